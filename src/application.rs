@@ -1,11 +1,22 @@
 use error::Error;
 use oauth;
 use openssl;
+use std::sync::Arc;
 
-pub trait Application {
+/// Signs requests to Xero on behalf of an application.
+///
+/// A client may be shared between threads, so applications must be `Send + Sync`; one which
+/// refreshes its access token should keep the token behind a `Mutex` or `RwLock`.
+pub trait Application: Send + Sync {
     fn get_signature(&self, url: &str, verb: &str) -> Result<String, Error>;
 }
 
+impl<A: Application + ?Sized> Application for Arc<A> {
+    fn get_signature(&self, url: &str, verb: &str) -> Result<String, Error> {
+        (**self).get_signature(url, verb)
+    }
+}
+
 pub struct PrivateApplication {
     oauth: oauth::Params,
     keypair: openssl::pkey::PKey,
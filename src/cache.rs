@@ -0,0 +1,130 @@
+use chrono::{DateTime, Utc};
+use response::ResponseMeta;
+use serde_json as json;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug)]
+pub struct CachedResponse {
+    pub body: Vec<u8>,
+    pub meta: ResponseMeta,
+    stored: Instant,
+    fetched_at: DateTime<Utc>,
+}
+
+impl CachedResponse {
+    /// Headers which ask Xero for the resource only if it changed since it was cached.
+    pub fn validators(&self) -> Vec<(String, String)> {
+        let mut headers = vec![(
+            String::from("If-Modified-Since"),
+            self.fetched_at.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+        )];
+        if let Some(etag) = self.meta.header("ETag") {
+            headers.push((String::from("If-None-Match"), etag.to_string()));
+        }
+        headers
+    }
+}
+
+/// Whether `path` is a collection, e.g. `/Contacts?where=...`, rather than a single resource
+/// such as `/Contacts/{id}` or `/Invoices/{id}/OnlineInvoice`.
+pub fn is_collection(path: &str) -> bool {
+    let path = path.split('?').next().unwrap_or("");
+    !path.trim_start_matches('/').contains('/')
+}
+
+/// Whether the response to a conditional request for `path` means the cached response is
+/// still current.
+///
+/// Xero answers `If-Modified-Since` on a collection with only the elements modified since, so
+/// an empty list means nothing changed. A single resource is either `304 Not Modified` or
+/// returned in full.
+pub fn is_unchanged(path: &str, body: &[u8], meta: &ResponseMeta) -> bool {
+    if meta.status == 304 {
+        return true;
+    }
+    if !is_collection(path) {
+        return false;
+    }
+    match json::from_slice::<json::Map<String, json::Value>>(body) {
+        Ok(object) => {
+            let mut lists = object.values().filter_map(|value| value.as_array()).peekable();
+            lists.peek().is_some() && lists.all(|list| list.is_empty())
+        }
+        Err(_) => false,
+    }
+}
+
+/// Caches responses to GET requests, e.g. for reference data such as items and accounts.
+///
+/// Responses are cached per tenant and path, e.g. `/Items`. Once a response is older than the cache's TTL it is
+/// revalidated with `If-Modified-Since`; a collection is only fetched again if it changed, and a
+/// single resource is replaced by the revalidation's response if it changed. Writes through a
+/// client invalidate the responses they could have changed. A cache can be shared by clients
+/// for different organisations, as long as each is given its own tenant.
+#[derive(Debug)]
+pub struct ResponseCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<(String, String), CachedResponse>>,
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration) -> ResponseCache {
+        ResponseCache{ttl: ttl, entries: Mutex::new(HashMap::new())}
+    }
+
+    /// The cached response to `path`, and whether it's still within the TTL.
+    pub(crate) fn get(&self, tenant: &str, path: &str) -> Option<(CachedResponse, bool)> {
+        self.lock().get(&(tenant.to_string(), path.to_string()))
+            .map(|cached| (cached.clone(), cached.stored.elapsed() < self.ttl))
+    }
+
+    pub(crate) fn insert(&self, tenant: &str, path: &str, body: Vec<u8>, meta: ResponseMeta) {
+        let fetched_at = meta.date.unwrap_or_else(Utc::now);
+        let cached = CachedResponse{body: body, meta: meta, stored: Instant::now(), fetched_at: fetched_at};
+        self.lock().insert((tenant.to_string(), path.to_string()), cached);
+    }
+
+    /// Restarts the TTL of a cached response which Xero said hasn't changed.
+    pub(crate) fn refresh(&self, tenant: &str, path: &str) {
+        if let Some(cached) = self.lock().get_mut(&(tenant.to_string(), path.to_string())) {
+            cached.stored = Instant::now();
+        }
+    }
+
+    /// Removes the tenant's cached responses for `collection`, e.g. `Contacts` removes
+    /// `/Contacts` and every `/Contacts/{id}`.
+    pub fn invalidate(&self, tenant: &str, collection: &str) {
+        let prefix = format!("/{}", collection);
+        self.lock().retain(|&(ref t, ref path), _| {
+            t != tenant || !path.starts_with(&prefix) || match path[prefix.len()..].chars().next() {
+                None | Some('/') | Some('?') => false,
+                _ => true,
+            }
+        });
+    }
+
+    /// Removes every cached response for a tenant.
+    pub fn invalidate_tenant(&self, tenant: &str) {
+        self.lock().retain(|&(ref t, _), _| t != tenant);
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// The collections a write to `collection` could change, e.g. a payment changes the amount
+    /// due on its invoice and the balances of its contact.
+    pub fn affected_collections(collection: &str) -> Vec<&str> {
+        match collection {
+            "Invoices" | "CreditNotes" | "ContactGroups" => vec![collection, "Contacts"],
+            "Payments" => vec!["Payments", "Invoices", "CreditNotes", "Prepayments", "Overpayments", "Contacts"],
+            _ => vec![collection],
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<HashMap<(String, String), CachedResponse>> {
+        self.entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
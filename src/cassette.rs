@@ -0,0 +1,161 @@
+//! Record requests to Xero and replay them later without a network.
+//!
+//! A cassette is a JSON file of request/response pairs. A `Recorder` wraps a real transport and
+//! saves every exchange, with `Authorization` headers removed; a `Replayer` answers each
+//! request with the first unused recorded response to the same method and URL.
+
+use error::Error;
+use rustc_serialize::base64::{self, FromBase64, ToBase64};
+use serde_json as json;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use transport::{Request, Response, Transport};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    #[serde(default)]
+    body: Body,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    #[serde(default)]
+    body: Body,
+}
+
+/// A body is saved as text so cassettes are easy to read and edit, unless it's binary.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Body {
+    Text(String),
+    Base64(String),
+}
+
+impl Default for Body {
+    fn default() -> Body {
+        Body::Text(String::new())
+    }
+}
+
+impl Body {
+    fn new(bytes: &[u8]) -> Body {
+        match String::from_utf8(bytes.to_vec()) {
+            Ok(text) => Body::Text(text),
+            Err(_) => Body::Base64(bytes.to_base64(base64::STANDARD)),
+        }
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        match *self {
+            Body::Text(ref text) => Ok(text.clone().into_bytes()),
+            Body::Base64(ref data) => data.from_base64().map_err(|err| Error::Conversion(Box::new(err))),
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Sends requests with another transport, saving each exchange to a cassette.
+pub struct Recorder<T: Transport> {
+    inner: T,
+    path: PathBuf,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+impl<T: Transport> Recorder<T> {
+    /// Records to the cassette at `path`, replacing it if it already exists.
+    pub fn new<P: AsRef<Path>>(inner: T, path: P) -> Recorder<T> {
+        Recorder{inner: inner, path: path.as_ref().to_path_buf(), interactions: Mutex::new(Vec::new())}
+    }
+}
+
+impl<T: Transport> Transport for Recorder<T> {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        let response = self.inner.send(request)?;
+        let interaction = Interaction{
+            request: RecordedRequest{
+                method: request.method.clone(),
+                url: request.url.clone(),
+                headers: request.headers.iter()
+                    .filter(|&&(ref name, _)| !name.eq_ignore_ascii_case("Authorization"))
+                    .cloned()
+                    .collect(),
+                body: Body::new(&request.body),
+            },
+            response: RecordedResponse{
+                status: response.status,
+                headers: response.headers.clone(),
+                body: Body::new(&response.body),
+            },
+        };
+
+        // The whole cassette is rewritten after each request, so it's complete even if the
+        // process exits without dropping the recorder.
+        let mut interactions = lock(&self.interactions);
+        interactions.push(interaction);
+        let mut file = File::create(&self.path)?;
+        json::to_writer_pretty(&mut file, &*interactions)?;
+        file.write_all(b"\n")?;
+        Ok(response)
+    }
+}
+
+/// Answers requests from a cassette, without sending them anywhere.
+pub struct Replayer {
+    interactions: Mutex<Vec<Option<Interaction>>>,
+}
+
+impl Replayer {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Replayer, Error> {
+        let mut data = String::new();
+        File::open(path)?.read_to_string(&mut data)?;
+        Replayer::from_json(&data)
+    }
+
+    pub fn from_json(data: &str) -> Result<Replayer, Error> {
+        let interactions: Vec<Interaction> = json::from_str(data)?;
+        Ok(Replayer{interactions: Mutex::new(interactions.into_iter().map(Some).collect())})
+    }
+
+    /// Whether every recorded response has been replayed.
+    pub fn is_finished(&self) -> bool {
+        lock(&self.interactions).iter().all(|i| i.is_none())
+    }
+}
+
+impl Transport for Replayer {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        let mut interactions = lock(&self.interactions);
+        let position = interactions.iter().position(|i| match *i {
+            Some(ref i) => i.request.method == request.method && i.request.url == request.url,
+            None => false,
+        });
+        let interaction = match position.and_then(|i| interactions[i].take()) {
+            Some(interaction) => interaction,
+            None => {
+                let message = format!("no recorded response for {} {}", request.method, request.url);
+                return Err(Error::Io(io::Error::new(io::ErrorKind::NotFound, message)));
+            }
+        };
+        Ok(Response{
+            status: interaction.response.status,
+            headers: interaction.response.headers,
+            body: interaction.response.body.to_bytes()?,
+        })
+    }
+}
@@ -1,67 +1,513 @@
 use application::Application;
+use cache::{self, ResponseCache};
 use error::{Error, RequestError};
 use hyper;
-use hyper::client::RequestBuilder;
-use hyper::header::{Accept, Headers};
-use hyper::net::HttpsConnector;
-use hyper_openssl::OpensslClient;
+use observer::{Observer, RequestEvent};
+use rand::{self, Rng};
+use rate_limit::RateLimiter;
+use response::ResponseMeta;
 use serde;
 use serde_json as json;
-use std::io::Read;
+use std::cell::{Cell, RefCell};
+use std::io::{self, Read};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use transport::{HyperTransport, Request, Transport};
+use uuid::Uuid;
 
+/// The Accounting API for public and private applications.
+pub const DEFAULT_BASE_URL: &str = "https://api.xero.com/api.xro/2.0";
+/// The Accounting API for partner applications, which must present their Entrust certificate.
+pub const PARTNER_BASE_URL: &str = "https://api-partner.network.xero.com/api.xro/2.0";
+
+thread_local! {
+    /// How many `run_with_meta` calls are in progress on this thread.
+    static CAPTURING: Cell<usize> = Cell::new(0);
+    /// The metadata of the last response received on this thread while capturing.
+    static LAST_META: RefCell<Option<ResponseMeta>> = RefCell::new(None);
+}
+
+/// Captures response metadata for one `run_with_meta` call, until dropped.
+struct Capture {
+    /// What an enclosing call had captured, or the last response captured by this one.
+    previous: Option<ResponseMeta>,
+}
+
+impl Capture {
+    fn start() -> Capture {
+        CAPTURING.with(|capturing| capturing.set(capturing.get() + 1));
+        Capture{previous: LAST_META.with(|last| last.borrow_mut().take())}
+    }
+
+    fn finish(mut self) -> Option<ResponseMeta> {
+        let meta = LAST_META.with(|last| last.borrow_mut().take());
+        if meta.is_some() {
+            self.previous = meta.clone();
+        }
+        meta
+    }
+}
+
+impl Drop for Capture {
+    fn drop(&mut self) {
+        let depth = CAPTURING.with(|capturing| {
+            capturing.set(capturing.get() - 1);
+            capturing.get()
+        });
+        // An enclosing call's last response is whichever was received most recently
+        let last = if depth > 0 { self.previous.take() } else { None };
+        LAST_META.with(|meta| *meta.borrow_mut() = last);
+    }
+}
+
+fn capture(meta: &ResponseMeta) {
+    if CAPTURING.with(|capturing| capturing.get()) > 0 {
+        LAST_META.with(|last| *last.borrow_mut() = Some(meta.clone()));
+    }
+}
+
+/// A client for the Xero API.
+///
+/// Clients are `Send + Sync`, so one client (and its rate limiter) can be shared between
+/// threads with an `Arc`.
 pub struct Client {
-    client: hyper::Client,
-    application: Box<Application>
+    transport: Box<Transport>,
+    application: Box<Application>,
+    retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    observers: Vec<Box<Observer>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    base_url: String,
+    cache: Option<(Arc<ResponseCache>, String)>,
 }
 
 impl Client {
-    fn url(path: &str) -> String {
-        format!("https://api.xero.com/api.xro/2.0/{}", &path[1..])
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Creates a client which sends its requests over HTTPS with default settings.
+    ///
+    /// To configure timeouts, a proxy or TLS, use `with_transport` with a transport built by
+    /// `HyperTransport::builder`.
+    pub fn new<App: Application + 'static>(app: App) -> Result<Client, Error> {
+        Ok(Client::with_transport(app, HyperTransport::new()?))
+    }
+
+    /// Creates a client which sends its requests with `transport` instead of over HTTPS.
+    pub fn with_transport<App, T>(app: App, transport: T) -> Client
+        where App: Application + 'static, T: Transport + 'static
+    {
+        Client {
+            transport: Box::new(transport),
+            application: Box::new(app),
+            retries: 0,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(30),
+            observers: Vec::new(),
+            rate_limiter: None,
+            base_url: DEFAULT_BASE_URL.to_string(),
+            cache: None,
+        }
+    }
+
+    /// Sends requests to another API root, e.g. `PARTNER_BASE_URL`.
+    pub fn with_base_url<S: Into<String>>(mut self, base_url: S) -> Client {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Retries requests up to `retries` times when they fail with a transient network error, or
+    /// Xero responds `429 Too Many Requests` or `503 Service Unavailable`.
+    ///
+    /// Writes are always retried with the same `Idempotency-Key`; one is generated for
+    /// writes which weren't given a key, so that a retry can't apply a write twice.
+    pub fn with_retries(mut self, retries: u32) -> Client {
+        self.retries = retries;
+        self
+    }
+
+    /// Sets the wait before the first retry, which doubles for each further retry up to `max`.
+    ///
+    /// Each wait is randomly shortened by up to half, so that clients which failed together
+    /// don't retry together. A `Retry-After` from Xero is waited for instead, unless it's longer
+    /// than `max` (e.g. the daily limit was reached), in which case the error is returned.
+    /// Defaults to 250ms and 30s.
+    pub fn with_retry_backoff(mut self, initial: Duration, max: Duration) -> Client {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Adds an observer which is notified of every request sent by the client.
+    pub fn with_observer<O: Observer + 'static>(mut self, observer: O) -> Client {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    /// Waits for `rate_limiter` before each request; it can be shared with other clients.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Client {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    pub fn rate_limiter(&self) -> Option<&Arc<RateLimiter>> {
+        self.rate_limiter.as_ref()
     }
 
-    pub fn new<App: Application + 'static>(app: App) -> Client {
-        let tls = OpensslClient::new().unwrap();
-        let connector = HttpsConnector::new(tls);
-        let client = hyper::Client::with_connector(connector);
-        Client {client: client, application: Box::new(app)}
+    /// Answers GET requests from `cache` where possible, under the organisation `tenant`.
+    ///
+    /// Writes sent by this client invalidate the tenant's cached responses they could change;
+    /// writes by anyone else are only seen once the cached response expires.
+    pub fn with_cache<S: Into<String>>(mut self, cache: Arc<ResponseCache>, tenant: S) -> Client {
+        self.cache = Some((cache, tenant.into()));
+        self
+    }
+
+    /// Runs `request`, returning its result with the metadata of the last response it received,
+    /// e.g. to log the correlation ID of an email sent with
+    /// `client.run_with_meta(|client| Invoice::email(client, id, None))`.
+    ///
+    /// Responses are captured on the calling thread, so `request` shouldn't send requests from
+    /// other threads. The metadata is `None` if nothing was sent, e.g. for an empty bulk write.
+    pub fn run_with_meta<T, F>(&self, request: F) -> Result<(T, Option<ResponseMeta>), Error>
+        where F: FnOnce(&Client) -> Result<T, Error>
+    {
+        let capture = Capture::start();
+        let value = request(self)?;
+        Ok((value, capture.finish()))
     }
 
     pub fn get<'a, T: serde::de::DeserializeOwned>(&'a self, path: &'a str) -> Result<T, Error> {
-        let url = Client::url(path);
-        let headers = self.headers("GET", &url)?;
-        let request = self.client.get(&url).headers(headers);
-        send(request)
+        self.get_with_meta(path).map(|(value, _)| value)
+    }
+
+    pub fn get_with_meta<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<(T, ResponseMeta), Error> {
+        let (body, meta) = match self.cache {
+            Some((ref cache, ref tenant)) => self.get_cached(cache, tenant, path)?,
+            None => self.get_raw(path, Vec::new())?,
+        };
+        Ok((json::from_slice(&body)?, meta))
+    }
+
+    /// Gets `path` from Xero even if it's cached, e.g. to check an invoice's current status
+    /// before changing it. The response replaces any cached one.
+    pub fn get_uncached<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+        let (body, meta) = self.get_raw(path, Vec::new())?;
+        if let Some((ref cache, ref tenant)) = self.cache {
+            cache.insert(tenant, path, body.clone(), meta);
+        }
+        Ok(json::from_slice(&body)?)
+    }
+
+    /// Gets a resource in a format other than JSON, e.g. `application/pdf`.
+    ///
+    /// Binary responses are never cached.
+    pub fn get_binary(&self, path: &str, accept: &str) -> Result<Vec<u8>, Error> {
+        self.get_binary_with_meta(path, accept).map(|(body, _)| body)
+    }
+
+    pub fn get_binary_with_meta(&self, path: &str, accept: &str) -> Result<(Vec<u8>, ResponseMeta), Error> {
+        let url = self.url(path);
+        self.retry(|| {
+            let headers = self.accept_headers(&url, accept)?;
+            self.send("GET", &url, headers, Vec::new())
+        })
+    }
+
+    /// Like `get_binary`, but returns the body as it's received instead of reading it into memory.
+    pub fn get_reader(&self, path: &str, accept: &str) -> Result<Box<Read + Send>, Error> {
+        let url = self.url(path);
+        self.retry(|| {
+            let headers = self.accept_headers(&url, accept)?;
+            self.send_streaming("GET", &url, headers).map(|(body, _)| body)
+        })
+    }
+
+    fn get_raw(&self, path: &str, extra_headers: Vec<(String, String)>) -> Result<(Vec<u8>, ResponseMeta), Error> {
+        let url = self.url(path);
+        self.retry(|| {
+            let mut headers = self.headers("GET", &url)?;
+            headers.extend(extra_headers.iter().cloned());
+            self.send("GET", &url, headers, Vec::new())
+        })
+    }
+
+    fn get_cached(&self, cache: &ResponseCache, tenant: &str, path: &str) -> Result<(Vec<u8>, ResponseMeta), Error> {
+        let cached = match cache.get(tenant, path) {
+            Some((cached, true)) => {
+                capture(&cached.meta);
+                return Ok((cached.body, cached.meta));
+            }
+            Some((cached, false)) => cached,
+            None => {
+                let (body, meta) = self.get_raw(path, Vec::new())?;
+                cache.insert(tenant, path, body.clone(), meta.clone());
+                return Ok((body, meta));
+            }
+        };
+
+        let (body, meta) = self.get_raw(path, cached.validators())?;
+        if cache::is_unchanged(path, &body, &meta) {
+            cache.refresh(tenant, path);
+            return Ok((cached.body, cached.meta));
+        }
+        // A list only holds the elements which changed, so the whole list is fetched again,
+        // but a single resource is already complete
+        let (body, meta) = if cache::is_collection(path) { self.get_raw(path, Vec::new())? } else { (body, meta) };
+        cache.insert(tenant, path, body.clone(), meta.clone());
+        Ok((body, meta))
+    }
+
+    pub fn put<T: serde::de::DeserializeOwned>(&self, path: &str, body: &[u8], idempotency_key: Option<&str>) -> Result<T, Error> {
+        self.put_with_meta(path, body, idempotency_key).map(|(value, _)| value)
+    }
+
+    pub fn put_with_meta<T: serde::de::DeserializeOwned>(&self, path: &str, body: &[u8], idempotency_key: Option<&str>) -> Result<(T, ResponseMeta), Error> {
+        self.write("PUT", path, body, idempotency_key)
+    }
+
+    pub fn post<T: serde::de::DeserializeOwned>(&self, path: &str, body: &[u8], idempotency_key: Option<&str>) -> Result<T, Error> {
+        self.post_with_meta(path, body, idempotency_key).map(|(value, _)| value)
+    }
+
+    pub fn post_with_meta<T: serde::de::DeserializeOwned>(&self, path: &str, body: &[u8], idempotency_key: Option<&str>) -> Result<(T, ResponseMeta), Error> {
+        self.write("POST", path, body, idempotency_key)
+    }
+
+    /// POSTs to an endpoint which doesn't respond with a resource, e.g. emailing an invoice.
+    pub fn post_empty(&self, path: &str, body: &[u8], idempotency_key: Option<&str>) -> Result<(), Error> {
+        self.post_empty_with_meta(path, body, idempotency_key).map(|_| ())
+    }
+
+    pub fn post_empty_with_meta(&self, path: &str, body: &[u8], idempotency_key: Option<&str>) -> Result<ResponseMeta, Error> {
+        let url = self.url(path);
+        let key = self.idempotency_key(idempotency_key);
+        let result = self.retry(|| {
+            let headers = self.write_headers("POST", &url, &key)?;
+            self.send("POST", &url, headers, body.to_vec()).map(|(_, meta)| meta)
+        });
+        self.invalidate(path);
+        result
+    }
+
+    pub fn delete(&self, path: &str, idempotency_key: Option<&str>) -> Result<(), Error> {
+        self.delete_with_meta(path, idempotency_key).map(|_| ())
+    }
+
+    pub fn delete_with_meta(&self, path: &str, idempotency_key: Option<&str>) -> Result<ResponseMeta, Error> {
+        let url = self.url(path);
+        let key = self.idempotency_key(idempotency_key);
+        let result = self.retry(|| {
+            let headers = self.write_headers("DELETE", &url, &key)?;
+            self.send("DELETE", &url, headers, Vec::new()).map(|(_, meta)| meta)
+        });
+        self.invalidate(path);
+        result
+    }
+
+    fn write<T: serde::de::DeserializeOwned>(&self, method: &str, path: &str, body: &[u8], idempotency_key: Option<&str>) -> Result<(T, ResponseMeta), Error> {
+        let url = self.url(path);
+        let key = self.idempotency_key(idempotency_key);
+        let result = self.retry(|| {
+            let headers = self.write_headers(method, &url, &key)?;
+            let (body, meta) = self.send(method, &url, headers, body.to_vec())?;
+            Ok((json::from_slice(&body)?, meta))
+        });
+        self.invalidate(path);
+        result
+    }
+
+    /// Sends a request, notifying any observers once it completes.
+    fn send(&self, method: &str, url: &str, headers: Vec<(String, String)>, body: Vec<u8>) -> Result<(Vec<u8>, ResponseMeta), Error> {
+        // Only a conditional GET can be answered with 304 Not Modified
+        let conditional = method == "GET" && headers.iter()
+            .any(|&(ref name, _)| name == "If-Modified-Since" || name == "If-None-Match");
+        let request = Request{method: method.to_string(), url: url.to_string(), headers: headers, body: body};
+        if let Some(ref rate_limiter) = self.rate_limiter {
+            rate_limiter.wait();
+        }
+        let start = Instant::now();
+        let mut response_size = 0;
+        let result = self.transport.send(&request).and_then(|response| {
+            response_size = response.body.len();
+            let meta = self.response_meta(response.status, response.headers);
+            capture(&meta);
+            match meta.status {
+                200...299 => Ok((response.body, meta)),
+                304 if conditional => Ok((response.body, meta)),
+                _ => Err(response_error(&response.body, meta)),
+            }
+        });
+
+        self.notify(request, start, &result, response_size);
+        result
+    }
+
+    /// Sends a request whose response body is returned unread, notifying any observers once
+    /// the response starts.
+    fn send_streaming(&self, method: &str, url: &str, headers: Vec<(String, String)>) -> Result<(Box<Read + Send>, ResponseMeta), Error> {
+        let request = Request{method: method.to_string(), url: url.to_string(), headers: headers, body: Vec::new()};
+        if let Some(ref rate_limiter) = self.rate_limiter {
+            rate_limiter.wait();
+        }
+        let start = Instant::now();
+        let mut response_size = 0;
+        let result = self.transport.send_streaming(&request).and_then(|mut response| {
+            let meta = self.response_meta(response.status, response.headers);
+            capture(&meta);
+            match meta.status {
+                200...299 => Ok((response.body, meta)),
+                _ => {
+                    let mut body = Vec::new();
+                    response.body.read_to_end(&mut body)?;
+                    response_size = body.len();
+                    Err(response_error(&body, meta))
+                }
+            }
+        });
+
+        self.notify(request, start, &result, response_size);
+        result
+    }
+
+    /// Reads a response's metadata, pausing the rate limiter if Xero asked to slow down.
+    fn response_meta(&self, status: u16, headers: Vec<(String, String)>) -> ResponseMeta {
+        let meta = ResponseMeta::new(status, headers);
+        if let (Some(rate_limiter), Some(seconds)) = (self.rate_limiter.as_ref(), meta.rate_limit.retry_after) {
+            rate_limiter.pause(Duration::from_secs(seconds as u64));
+        }
+        meta
+    }
+
+    fn notify<T>(&self, request: Request, start: Instant, result: &Result<(T, ResponseMeta), Error>, response_size: usize) {
+        if self.observers.is_empty() {
+            return;
+        }
+        let redacted: Vec<(String, String)> = request.headers.into_iter()
+            .filter(|&(ref name, _)| !name.eq_ignore_ascii_case("Authorization"))
+            .collect();
+        let event = RequestEvent{
+            method: &request.method,
+            url: &request.url,
+            headers: &redacted,
+            body_size: request.body.len(),
+            latency: start.elapsed(),
+            response: match *result {
+                Ok((_, ref meta)) | Err(Error::Xero(_, ref meta)) => Some(meta),
+                Err(_) => None,
+            },
+            response_size: response_size,
+        };
+        for observer in &self.observers {
+            observer.observe(&event);
+        }
+    }
+
+    /// Removes the cached responses a write to `path` could change.
+    fn invalidate(&self, path: &str) {
+        if let Some((ref cache, ref tenant)) = self.cache {
+            let collection = path[1..].split(|c| c == '/' || c == '?').next().unwrap_or("");
+            for affected in ResponseCache::affected_collections(collection) {
+                cache.invalidate(tenant, affected);
+            }
+        }
     }
 
-    pub fn put<T: serde::de::DeserializeOwned>(&self, path: &str, body: &[u8]) -> Result<T, Error> {
-        let url = Client::url(path);
-        let headers = self.headers("PUT", &url)?;
-        let request = self.client.put(&url).headers(headers).body(body);
-        send(request)
+    fn idempotency_key(&self, key: Option<&str>) -> Option<String> {
+        match key {
+            Some(key) => Some(key.to_string()),
+            None if self.retries > 0 => Some(Uuid::new_v4().to_string()),
+            None => None,
+        }
     }
 
-    fn headers(&self, method: &str, url: &str) -> Result<Headers, Error> {
+    fn retry<T, F: FnMut() -> Result<T, Error>>(&self, mut request: F) -> Result<T, Error> {
+        let mut attempt = 0;
+        loop {
+            let result = request();
+            let delay = match result {
+                Err(ref err) if attempt < self.retries => self.retry_delay(err, attempt),
+                _ => None,
+            };
+            match delay {
+                Some(delay) => {
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                None => return result,
+            }
+        }
+    }
+
+    /// How long to wait before retrying a request which failed with `error`, if it should be.
+    fn retry_delay(&self, error: &Error, attempt: u32) -> Option<Duration> {
+        match *error {
+            Error::Http(hyper::Error::Io(ref err)) | Error::Io(ref err) if is_transient(err) => Some(self.backoff(attempt)),
+            Error::Xero(_, ref meta) if meta.status == 429 || meta.status == 503 => match meta.rate_limit.retry_after {
+                Some(seconds) if Duration::from_secs(seconds as u64) > self.max_backoff => None,
+                // The rate limiter was paused for as long as Xero asked when the response arrived
+                Some(_) if self.rate_limiter.is_some() => Some(Duration::from_secs(0)),
+                Some(seconds) => Some(Duration::from_secs(seconds as u64)),
+                None => Some(self.backoff(attempt)),
+            },
+            _ => None,
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self.initial_backoff.checked_mul(1 << attempt.min(16))
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff));
+        let millis = backoff.as_secs() * 1000 + backoff.subsec_nanos() as u64 / 1_000_000;
+        Duration::from_millis(millis - rand::thread_rng().gen_range(0, millis / 2 + 1))
+    }
+
+    fn headers(&self, method: &str, url: &str) -> Result<Vec<(String, String)>, Error> {
         // TODO: Add body to signature
         let signature = self.application.get_signature(method, url)?;
-        let mut headers = Headers::new();
-        headers.set(Accept::json());
-        headers.set_raw("Authorization", vec![signature.as_bytes().to_vec()]);
+        Ok(vec![
+            (String::from("Accept"), String::from("application/json")),
+            (String::from("Authorization"), signature),
+        ])
+    }
+
+    fn accept_headers(&self, url: &str, accept: &str) -> Result<Vec<(String, String)>, Error> {
+        let mut headers = self.headers("GET", url)?;
+        for header in headers.iter_mut().filter(|&&mut (ref name, _)| name == "Accept") {
+            header.1 = accept.to_string();
+        }
         Ok(headers)
     }
-}
 
-fn send<T: serde::de::DeserializeOwned>(request: RequestBuilder) -> Result<T, Error> {
-    let mut response = request.send()?;
-    let mut body = String::with_capacity(4096);
-    response.read_to_string(&mut body)?;
-    let status = response.status_raw().0;
-    match status {
-        200...299 => {}
-        _ => {
-            let error = json::from_str(&body).unwrap_or_else(|_| { RequestError::UnknownError(body) });
-            return Err(Error::from(error));
+    fn write_headers(&self, method: &str, url: &str, idempotency_key: &Option<String>) -> Result<Vec<(String, String)>, Error> {
+        let mut headers = self.headers(method, url)?;
+        if let Some(ref key) = *idempotency_key {
+            headers.push((String::from("Idempotency-Key"), key.clone()));
         }
+        Ok(headers)
     }
+}
+
+/// Whether a request which failed with `err` might succeed if sent again.
+fn is_transient(err: &io::Error) -> bool {
+    matches!(err.kind(),
+        io::ErrorKind::ConnectionRefused | io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted |
+        io::ErrorKind::NotConnected | io::ErrorKind::BrokenPipe | io::ErrorKind::TimedOut |
+        io::ErrorKind::Interrupted | io::ErrorKind::UnexpectedEof)
+}
+
+/// The error Xero reported in an unsuccessful response.
+fn response_error(body: &[u8], meta: ResponseMeta) -> Error {
+    let error = json::from_slice(body).unwrap_or_else(|_| {
+        RequestError::UnknownError(String::from_utf8_lossy(body).into_owned())
+    });
+    Error::Xero(error, meta)
+}
 
-    json::from_str(&body).map_err(|err| Error::from(err))
+#[allow(dead_code)]
+fn assert_send_sync() {
+    fn assert<T: Send + Sync>() {}
+    assert::<Client>();
 }
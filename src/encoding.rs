@@ -1,4 +1,8 @@
 use bigdecimal::BigDecimal;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer};
+use serde::de;
+use std::io;
 use xml::EventWriter;
 use xml::writer::{EmitterConfig, XmlEvent};
 
@@ -45,7 +49,7 @@ impl<'a> XmlWriter<'a> {
         }
     }
 
-    pub fn array<T: XmlSerializable>(&mut self, array: &str, element: &str, items: &Vec<T>) -> Result<(), XmlError> {
+    pub fn array<T: XmlSerializable>(&mut self, array: &str, element: &str, items: &[T]) -> Result<(), XmlError> {
         self.start_element(array)?;
         for item in items {
             self.element(element, item)?;
@@ -63,7 +67,7 @@ pub trait XmlSerializable {
             let mut writer = XmlWriter::new(&mut body);
             self.write(&mut writer)?;
         }
-        Ok(String::from_utf8(body).unwrap()) // FIXME: Don't unwrap? or document panic
+        String::from_utf8(body).map_err(|err| XmlError::from(io::Error::new(io::ErrorKind::InvalidData, err)))
     }
 }
 
@@ -118,3 +122,21 @@ impl XmlSerializable for BigDecimal {
         xml.write(&self.with_scale(4).to_string())
     }
 }
+
+/// Parses a timestamp in the format Xero uses in JSON, e.g. `/Date(1439434356790+0000)/`.
+pub fn parse_json_date(s: &str) -> Option<DateTime<Utc>> {
+    let inner = s.trim().trim_start_matches("/Date(").trim_end_matches(")/");
+    let end = inner.char_indices().skip(1).find(|&(_, c)| c == '+' || c == '-').map_or(inner.len(), |(i, _)| i);
+    let millis: i64 = inner[..end].parse().ok()?;
+    let secs = if millis < 0 { (millis - 999) / 1000 } else { millis / 1000 };
+    let nanos = (millis - secs * 1000) as u32 * 1_000_000;
+    NaiveDateTime::from_timestamp_opt(secs, nanos).map(|date| DateTime::from_utc(date, Utc))
+}
+
+/// Deserializes an optional JSON timestamp such as `UpdatedDateUTC`, for use with `deserialize_with`.
+pub fn deserialize_json_date_opt<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error> {
+    match Option::<String>::deserialize(deserializer)? {
+        Some(s) => parse_json_date(&s).map(Some).ok_or_else(|| de::Error::custom(format!("invalid date {:?}", s))),
+        None => Ok(None),
+    }
+}
@@ -1,16 +1,21 @@
 use hyper;
 use oauth;
+use openssl;
+use resources::invoices::InvoiceError;
+use response::ResponseMeta;
+use serde::{Deserialize, Deserializer};
 use serde_json as json;
 use std::error;
 use std::fmt;
 use std::io;
+use tax_numbers::TaxNumberError;
 use xml;
 
 /// An error encountered when communicating with the Xero API.
 #[derive(Debug)]
 pub enum Error {
-    /// An error reported by Xero.
-    Xero(RequestError),
+    /// An error reported by Xero, with the response it was reported in.
+    Xero(RequestError, ResponseMeta),
     /// A networking error communicating with the Xero server.
     Http(hyper::Error),
     /// An error reading the response body.
@@ -19,17 +24,36 @@ pub enum Error {
     OAuth(oauth::Error),
     /// An error converting between wire format and Rust types.
     Conversion(Box<error::Error + Send>),
+    /// A successful response which didn't contain the expected resource.
+    EmptyResponse,
+    /// An error configuring TLS.
+    Tls(openssl::error::ErrorStack),
+    /// A tax number which was rejected before being sent to Xero.
+    InvalidTaxNumber(TaxNumberError),
+    /// An action refused for an invoice, with the response it was reported in, or `None` if
+    /// it was refused before being sent to Xero.
+    Invoice(InvoiceError, Option<ResponseMeta>),
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(error::Error::description(self))?;
         match *self {
-            Error::Xero(ref err) => write!(f, ": {}", err),
+            Error::Xero(ref err, ref meta) => {
+                write!(f, ": {}", err)?;
+                match meta.correlation_id {
+                    Some(ref id) => write!(f, " (correlation id {})", id),
+                    None => Ok(()),
+                }
+            }
             Error::Http(ref err) => write!(f, ": {}", err),
             Error::Io(ref err) => write!(f, ": {}", err),
             Error::OAuth(ref err) => write!(f, ": {}", err),
             Error::Conversion(ref err) => write!(f, ": {}", err),
+            Error::EmptyResponse => Ok(()),
+            Error::Tls(ref err) => write!(f, ": {}", err),
+            Error::InvalidTaxNumber(ref err) => write!(f, ": {}", err),
+            Error::Invoice(ref err, _) => write!(f, ": {}", err),
         }
     }
 }
@@ -37,28 +61,41 @@ impl fmt::Display for Error {
 impl error::Error for Error {
     fn description(&self) -> &str {
         match *self {
-            Error::Xero(_) => "error reported by xero",
+            Error::Xero(..) => "error reported by xero",
             Error::Http(_) => "error communicating with xero",
             Error::Io(_) => "error reading response from xero",
             Error::OAuth(_) => "error performing oauth with xero",
             Error::Conversion(_) => "error converting between wire format and Rust types",
+            Error::EmptyResponse => "response from xero was missing the expected resource",
+            Error::Tls(_) => "error configuring tls",
+            Error::InvalidTaxNumber(_) => "invalid tax number",
+            Error::Invoice(..) => "invoice action refused",
         }
     }
 
     fn cause(&self) -> Option<&error::Error> {
         match *self {
-            Error::Xero(ref err) => Some(err),
+            Error::Xero(ref err, _) => Some(err),
             Error::Http(ref err) => Some(err),
             Error::Io(ref err) => Some(err),
             Error::OAuth(ref err) => Some(err),
             Error::Conversion(ref err) => Some(&**err),
+            Error::EmptyResponse => None,
+            Error::Tls(ref err) => Some(err),
+            Error::InvalidTaxNumber(ref err) => Some(err),
+            Error::Invoice(ref err, _) => Some(err),
         }
     }
 }
 
-impl From<RequestError> for Error {
-    fn from(err: RequestError) -> Error {
-        Error::Xero(err)
+impl Error {
+    /// The response which the error was reported in, if the error was reported by Xero.
+    pub fn meta(&self) -> Option<&ResponseMeta> {
+        match *self {
+            Error::Xero(_, ref meta) => Some(meta),
+            Error::Invoice(_, Some(ref meta)) => Some(meta),
+            _ => None,
+        }
     }
 }
 
@@ -80,6 +117,18 @@ impl From<oauth::Error> for Error {
     }
 }
 
+impl From<openssl::error::ErrorStack> for Error {
+    fn from(err: openssl::error::ErrorStack) -> Error {
+        Error::Tls(err)
+    }
+}
+
+impl From<TaxNumberError> for Error {
+    fn from(err: TaxNumberError) -> Error {
+        Error::InvalidTaxNumber(err)
+    }
+}
+
 impl From<xml::writer::Error> for Error {
     fn from(err: xml::writer::Error) -> Error {
         Error::Conversion(Box::new(err))
@@ -92,20 +141,29 @@ impl From<json::Error> for Error {
     }
 }
 
-/// An error reported by Xero in a request's response.
-#[derive(Debug,Deserialize)]
+/// A validation message attached to a submitted element.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct ErrorMessage {
-    message: String,
+    pub message: String,
 }
-#[derive(Debug,Deserialize)]
+
+/// A submitted element (contact, invoice, ...) as echoed back by Xero in an error response.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct ErrorElement {
-    has_validation_errors: bool,
-    validation_errors: Vec<ErrorMessage>,
-    // warnings: Vec<_>,
+    /// The position of the element in the submitted request.
+    #[serde(skip)]
+    pub index: usize,
+    #[serde(default)]
+    pub has_validation_errors: bool,
+    #[serde(default)]
+    pub validation_errors: Vec<ErrorMessage>,
+    #[serde(default)]
+    pub warnings: Vec<ErrorMessage>,
 }
 
+/// An error reported by Xero in a request's response.
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 pub enum RequestError {
@@ -117,34 +175,82 @@ pub enum RequestError {
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct StatusError {
-    id: String,
-    status: String,
+    pub id: String,
+    pub status: String,
     #[serde(default)]
-    provider_name: String,
+    pub provider_name: String,
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ValidationError {
-    error_number: i64,
+    pub error_number: i64,
     #[serde(rename = "Type")]
-    error_type: String,
+    pub error_type: String,
     #[serde(default)]
-    message: String,
+    pub message: String,
+    /// Every submitted element, in the order it was submitted.
     #[serde(default)]
-    elements: Vec<ErrorElement>,
+    #[serde(deserialize_with = "deserialize_elements")]
+    pub elements: Vec<ErrorElement>,
+}
+
+impl ValidationError {
+    /// The submitted elements which Xero rejected.
+    pub fn failed_elements(&self) -> Vec<&ErrorElement> {
+        self.elements.iter().filter(|e| e.has_validation_errors || !e.validation_errors.is_empty()).collect()
+    }
+}
+
+fn deserialize_elements<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<ErrorElement>, D::Error> {
+    let mut elements = Vec::<ErrorElement>::deserialize(deserializer)?;
+    for (index, element) in elements.iter_mut().enumerate() {
+        element.index = index;
+    }
+    Ok(elements)
+}
+
+impl fmt::Display for ErrorElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "element {}", self.index)?;
+        for (i, error) in self.validation_errors.iter().enumerate() {
+            f.write_str(if i == 0 { ": " } else { "; " })?;
+            f.write_str(&error.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.status)?;
+        if !self.provider_name.is_empty() {
+            write!(f, " ({})", self.provider_name)?;
+        }
+        Ok(())
+    }
 }
 
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.error_type, self.error_number)?;
+        if !self.message.is_empty() {
+            write!(f, ": {}", self.message)?;
+        }
+        for element in self.failed_elements() {
+            write!(f, " [{}]", element)?;
+        }
+        Ok(())
+    }
+}
 
 impl fmt::Display for RequestError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.write_str(error::Error::description(self))?;
-
-        // TODO: Implement Display for StatusError and ValidationError
         match *self {
-            RequestError::Status(ref err) => write!(f, ": {:?}", err),
-            RequestError::Validation(ref err) => write!(f, ": {:?}", err),
-            RequestError::UnknownError(ref err) => write!(f, ": {:?}", err),
+            RequestError::Status(ref err) => write!(f, ": {}", err),
+            RequestError::Validation(ref err) => write!(f, ": {}", err),
+            RequestError::UnknownError(ref err) => write!(f, ": {}", err),
         }
     }
 }
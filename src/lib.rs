@@ -10,6 +10,8 @@ extern crate bigdecimal;
 extern crate chrono;
 extern crate hyper;
 extern crate hyper_openssl;
+#[macro_use]
+extern crate log;
 extern crate openssl;
 #[macro_use]
 extern crate percent_encoding;
@@ -18,24 +20,49 @@ extern crate rustc_serialize;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+#[cfg_attr(feature = "testing", macro_use)]
 extern crate serde_json;
 extern crate serde_urlencoded;
+extern crate uuid;
 extern crate xml;
 
 mod application;
+mod cache;
+pub mod cassette;
 mod client;
 pub mod encoding;
 mod error;
 mod oauth;
+mod observer;
+mod rate_limit;
 mod resources;
+mod response;
+mod tax_numbers;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod transport;
 
 pub use application::{Application, PrivateApplication};
-pub use client::Client;
+pub use cache::ResponseCache;
+pub use client::{Client, DEFAULT_BASE_URL, PARTNER_BASE_URL};
+pub use error::{Error, ErrorElement, ErrorMessage, RequestError, StatusError, ValidationError};
+pub use observer::{LogObserver, Observer, RequestEvent};
+pub use rate_limit::RateLimiter;
+pub use response::{RateLimitStatus, ResponseMeta};
+pub use transport::{HyperTransport, HyperTransportBuilder, Request, Response, StreamingResponse, Transport};
 pub use openssl::rsa::Rsa;
 pub use openssl::pkey::PKey;
 pub mod accounting {
+    pub use resources::bulk::{BulkParams, BulkResults, BulkWriter, SavedElement};
+    pub use resources::contact_groups::*;
+    pub use resources::contact_merge::*;
     pub use resources::contacts::*;
+    pub use resources::currencies::*;
+    pub use resources::history::*;
+    pub use resources::ids::*;
+    pub use resources::invoice_totals::*;
     pub use resources::invoices::*;
     pub use resources::items::*;
     pub use resources::payments::*;
+    pub use tax_numbers::*;
 }
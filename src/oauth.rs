@@ -11,7 +11,7 @@ mod percent {
     define_encode_set! {
         // All non alphanumeric characters on the (US) keyboard, except '~', '-', '_', and '.'
         pub PERCENT_ENCODE_SET = [SIMPLE_ENCODE_SET]
-            | {'`', '!', '@', '#', '$', '%', '^', '&', '*', '(', ')', '+', '=', '[', ']', '{', '}', '|', '\\', ';', ':', '\'', '"', ',', '<', '>', '/', '?'}
+            | {' ', '`', '!', '@', '#', '$', '%', '^', '&', '*', '(', ')', '+', '=', '[', ']', '{', '}', '|', '\\', ';', ':', '\'', '"', ',', '<', '>', '/', '?'}
     }
 
     pub fn to_string(value: &str) -> String {
@@ -59,14 +59,19 @@ impl Params {
         })
     }
 
-    // TODO: Handle url with query paramters
-    pub fn sign_request(&self, keypair: &openssl::pkey::PKey, method: &str, base_url: &str) -> Result<String, Error> {
+    pub fn sign_request(&self, keypair: &openssl::pkey::PKey, method: &str, url: &str) -> Result<String, Error> {
         let signature: String;
         let nonce = generate_nonce()?;
         let timestamp = generate_timestamp();
         let mut params = self.get_oauth_params(&nonce, &timestamp);
 
-        let message = self.get_signature_base(method, base_url, &urlencoded::to_string(&params)?)?;
+        // Query parameters are signed along with the oauth parameters, but not sent in the header
+        let (base_url, query) = match url.find('?') {
+            Some(i) => (&url[..i], &url[i + 1..]),
+            None => (url, ""),
+        };
+        let normalized = normalize_params(&params, query)?;
+        let message = self.get_signature_base(method, base_url, &normalized)?;
         let mut signer = openssl::sign::Signer::new(openssl::hash::MessageDigest::sha1(), keypair)?;
         signer.update(message.as_bytes())?;
         let signature_bytes = signer.finish()?;
@@ -106,6 +111,17 @@ impl Params {
     }
 }
 
+fn normalize_params(params: &[(&str, &str)], query: &str) -> Result<String, Error> {
+    let query: Vec<(String, String)> = urlencoded::from_str(query)?;
+    let mut encoded: Vec<(String, String)> = params.iter()
+        .map(|&(key, value)| (percent::to_string(key), percent::to_string(value)))
+        .chain(query.iter().map(|&(ref key, ref value)| (percent::to_string(key), percent::to_string(value))))
+        .collect();
+    encoded.sort();
+    let formatted: Vec<String> = encoded.iter().map(|&(ref key, ref value)| format!("{}={}", key, value)).collect();
+    Ok(formatted.join("&"))
+}
+
 fn generate_nonce() -> Result<String, Error> {
     let alphabet = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
     let mut rng = rand::OsRng::new()?;
@@ -148,6 +164,12 @@ impl From<urlencoded::ser::Error> for Error {
     }
 }
 
+impl From<urlencoded::de::Error> for Error {
+    fn from(err: urlencoded::de::Error) -> Error {
+        Error(Box::new(err))
+    }
+}
+
 impl From<openssl::error::ErrorStack> for Error {
     fn from(err: openssl::error::ErrorStack) -> Error {
         Error(Box::new(err))
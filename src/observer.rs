@@ -0,0 +1,55 @@
+use response::ResponseMeta;
+use std::time::Duration;
+
+/// A request sent to Xero, and the response if one was received.
+#[derive(Debug)]
+pub struct RequestEvent<'a> {
+    pub method: &'a str,
+    pub url: &'a str,
+    /// The request headers, without `Authorization`.
+    pub headers: &'a [(String, String)],
+    pub body_size: usize,
+    pub latency: Duration,
+    /// The response, or `None` if the request failed before Xero responded.
+    pub response: Option<&'a ResponseMeta>,
+    /// The size of the response body, including error responses; 0 if no response was received,
+    /// or a successful response was streamed (e.g. `Invoice::pdf_reader`) rather than read by the
+    /// client.
+    pub response_size: usize,
+}
+
+/// Observes every request a `Client` sends, e.g. for logging or metrics.
+pub trait Observer: Send + Sync {
+    fn observe(&self, event: &RequestEvent);
+}
+
+/// Logs each request with the `log` crate under the `xero` target.
+///
+/// Requests are logged at `info`, or `warn` if they failed; headers are logged at `debug`.
+pub struct LogObserver;
+
+impl Observer for LogObserver {
+    fn observe(&self, event: &RequestEvent) {
+        let millis = event.latency.as_secs() * 1000 + (event.latency.subsec_nanos() / 1_000_000) as u64;
+        match event.response {
+            Some(meta) if meta.status < 400 => {
+                info!(target: "xero", "{} {} {} ({} bytes sent, {} bytes received) in {}ms, correlation id {}",
+                      event.method, event.url, meta.status, event.body_size, event.response_size, millis,
+                      meta.correlation_id.as_ref().map(|id| id.as_str()).unwrap_or("-"));
+            }
+            Some(meta) => {
+                warn!(target: "xero", "{} {} {} ({} bytes sent, {} bytes received) in {}ms, correlation id {}",
+                      event.method, event.url, meta.status, event.body_size, event.response_size, millis,
+                      meta.correlation_id.as_ref().map(|id| id.as_str()).unwrap_or("-"));
+            }
+            None => {
+                warn!(target: "xero", "{} {} failed ({} bytes sent) after {}ms",
+                      event.method, event.url, event.body_size, millis);
+            }
+        }
+        debug!(target: "xero", "request headers: {:?}", event.headers);
+        if let Some(meta) = event.response {
+            debug!(target: "xero", "response headers: {:?}", meta.headers);
+        }
+    }
+}
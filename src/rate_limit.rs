@@ -0,0 +1,77 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The most calls Xero allows per organisation each minute.
+pub const DEFAULT_CALLS_PER_MINUTE: usize = 60;
+
+#[derive(Debug, Default)]
+struct State {
+    sent: VecDeque<Instant>,
+    paused_until: Option<Instant>,
+}
+
+/// Limits how often requests are sent, so that a client stays within Xero's rate limits.
+///
+/// Requests wait for a free slot instead of failing. If Xero reports a limit was exceeded
+/// anyway, e.g. because another process shares the organisation, requests wait for as long
+/// as its `Retry-After` header asks.
+#[derive(Debug)]
+pub struct RateLimiter {
+    calls: usize,
+    period: Duration,
+    state: Mutex<State>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> RateLimiter {
+        RateLimiter::new(DEFAULT_CALLS_PER_MINUTE, Duration::from_secs(60))
+    }
+}
+
+impl RateLimiter {
+    /// Allows at most `calls` requests in any `period`.
+    pub fn new(calls: usize, period: Duration) -> RateLimiter {
+        RateLimiter{calls: calls.max(1), period: period, state: Mutex::new(State::default())}
+    }
+
+    /// Blocks until a request can be sent without exceeding the limit, then counts it as sent.
+    pub fn wait(&self) {
+        while let Err(delay) = self.try_acquire_at(Instant::now()) {
+            thread::sleep(delay);
+        }
+    }
+
+    /// Counts a request as sent at `now` if that doesn't exceed the limit, otherwise returns
+    /// how long to wait before trying again.
+    ///
+    /// `now` must not be earlier than the time given to any previous call.
+    pub fn try_acquire_at(&self, now: Instant) -> Result<(), Duration> {
+        let mut state = self.lock();
+        while state.sent.front().map_or(false, |&sent| now.duration_since(sent) >= self.period) {
+            state.sent.pop_front();
+        }
+        match state.paused_until {
+            Some(until) if until > now => Err(until.duration_since(now)),
+            _ if state.sent.len() < self.calls => {
+                state.sent.push_back(now);
+                Ok(())
+            }
+            _ => Err(self.period - now.duration_since(state.sent[0])),
+        }
+    }
+
+    /// Holds back every request for `duration`, e.g. after Xero responds with `Retry-After`.
+    pub fn pause(&self, duration: Duration) {
+        let until = Instant::now() + duration;
+        let mut state = self.lock();
+        if state.paused_until.map_or(true, |paused| paused < until) {
+            state.paused_until = Some(until);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
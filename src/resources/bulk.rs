@@ -0,0 +1,169 @@
+use client::Client;
+use encoding::{XmlSerializable, XmlWriter};
+use error::{Error, ErrorElement, ErrorMessage};
+use serde::de::DeserializeOwned;
+use serde_json as json;
+use std::thread;
+use std::time::Duration;
+
+/// The most elements Xero recommends sending in a single request.
+pub const DEFAULT_BATCH_SIZE: usize = 50;
+/// The largest request body Xero accepts, in bytes.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 3_500_000;
+
+/// Params for a resource which can be created in bulk with a single request.
+pub trait BulkParams: XmlSerializable {
+    /// The resource returned by Xero for each element.
+    type Resource: DeserializeOwned;
+
+    /// The endpoint elements are sent to, e.g. `/Contacts`.
+    fn endpoint() -> &'static str;
+    /// The name wrapping the list of elements, e.g. `Contacts`.
+    fn collection() -> &'static str;
+    /// The name of each element, e.g. `Contact`.
+    fn element() -> &'static str;
+
+    /// Checks the element before it's sent, rather than leaving Xero to reject it.
+    fn validate(&self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// An element which Xero saved, with any warnings it reported about it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SavedElement<R> {
+    /// The position of the element in the submitted request.
+    pub index: usize,
+    pub resource: R,
+    pub warnings: Vec<ErrorMessage>,
+}
+
+/// The result of writing each element of a bulk request.
+pub type BulkResults<R> = Vec<Result<SavedElement<R>, ErrorElement>>;
+
+/// PUTs `params` with `summarizeErrors=false`, so that valid elements are created even when
+/// others fail validation.
+///
+/// The results are in the same order as `params`. Nothing is sent if any element fails
+/// `BulkParams::validate`.
+pub fn put_each<P: BulkParams>(client: &Client, params: &[P], idempotency_key: Option<&str>) -> Result<BulkResults<P::Resource>, Error> {
+    for params in params {
+        params.validate()?;
+    }
+    let mut body = Vec::new();
+    {
+        let mut xml = XmlWriter::new(&mut body);
+        xml.array(P::collection(), P::element(), params)?;
+    }
+    let path = format!("{}?summarizeErrors=false", P::endpoint());
+    let mut response: json::Map<String, json::Value> = client.put(&path, body.as_slice(), idempotency_key)?;
+    let elements: Vec<json::Value> = json::from_value(response.remove(P::collection()).unwrap_or(json::Value::Null))?;
+
+    elements.into_iter().enumerate().map(|(index, mut element)| {
+        let failed = element.get("StatusAttributeString").and_then(|s| s.as_str()) == Some("ERROR");
+        if failed {
+            let mut error: ErrorElement = json::from_value(element)?;
+            error.index = index;
+            Ok(Err(error))
+        } else {
+            let warnings = match element.as_object_mut().and_then(|element| element.remove("Warnings")) {
+                Some(warnings) => json::from_value(warnings)?,
+                None => Vec::new(),
+            };
+            Ok(Ok(SavedElement{index: index, resource: json::from_value(element)?, warnings: warnings}))
+        }
+    }).collect()
+}
+
+/// Writes any number of elements by splitting them into batches that respect Xero's limits.
+///
+/// Batches are sent one after another so that large imports stay under Xero's per-minute rate
+/// limit: through the client's `RateLimiter` if it has one, otherwise with a delay between them.
+pub struct BulkWriter<'a> {
+    client: &'a Client,
+    batch_size: usize,
+    max_body_size: usize,
+    delay: Duration,
+}
+
+impl<'a> BulkWriter<'a> {
+    pub fn new(client: &'a Client) -> BulkWriter<'a> {
+        BulkWriter{
+            client: client,
+            batch_size: DEFAULT_BATCH_SIZE,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            delay: Duration::from_secs(1),
+        }
+    }
+
+    /// Sets the most elements sent in one request.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = if batch_size > 0 { batch_size } else { 1 };
+        self
+    }
+
+    /// Sets the largest body sent in one request; an element larger than this is sent alone.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Sets how long to wait between batches when the client has no `RateLimiter`.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// PUTs every element of `params`, see `put_each`.
+    ///
+    /// The results are merged back into the same order as `params`. If a whole batch fails
+    /// (e.g. a network error) the error is returned and later batches aren't sent.
+    ///
+    /// Each batch is sent with its own idempotency key, derived from `idempotency_key` and the
+    /// batch's position, so that the same `params` can be safely resubmitted after a failure.
+    /// Every element is validated before the first batch is sent.
+    pub fn put<P: BulkParams>(&self, params: Vec<P>, idempotency_key: Option<&str>) -> Result<BulkResults<P::Resource>, Error> {
+        for params in &params {
+            params.validate()?;
+        }
+        let mut results = Vec::with_capacity(params.len());
+        let mut offset = 0;
+        for (i, batch) in self.batches(params)?.into_iter().enumerate() {
+            if i > 0 && self.client.rate_limiter().is_none() {
+                thread::sleep(self.delay);
+            }
+            let batch_key = idempotency_key.map(|key| format!("{}-{}", key, i));
+            for result in put_each(self.client, &batch, batch_key.as_ref().map(|key| key.as_str()))? {
+                results.push(match result {
+                    Ok(mut saved) => { saved.index += offset; Ok(saved) }
+                    Err(mut error) => { error.index += offset; Err(error) }
+                });
+            }
+            offset += batch.len();
+        }
+        Ok(results)
+    }
+
+    /// Splits `params` into the batches `put` sends, in order.
+    pub fn batches<P: BulkParams>(&self, params: Vec<P>) -> Result<Vec<Vec<P>>, Error> {
+        // Leave room for the wrapping collection and element tags
+        let overhead = 2 * (P::collection().len() + 3);
+        let mut batches = Vec::new();
+        let mut batch = Vec::new();
+        let mut size = overhead;
+        for param in params {
+            let len = param.to_xml()?.len() + 2 * (P::element().len() + 3);
+            if !batch.is_empty() && (batch.len() >= self.batch_size || size + len > self.max_body_size) {
+                batches.push(batch);
+                batch = Vec::new();
+                size = overhead;
+            }
+            size += len;
+            batch.push(param);
+        }
+        if !batch.is_empty() {
+            batches.push(batch);
+        }
+        Ok(batches)
+    }
+}
@@ -0,0 +1,131 @@
+use client::Client;
+use encoding::{XmlError, XmlSerializable, XmlWriter};
+use error::Error;
+use resources::contacts::{ContactIdParams, ContactSummary};
+use resources::ids::{ContactGroupId, ContactId};
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ContactGroupStatus {
+    Active,
+    Deleted,
+}
+
+impl XmlSerializable for ContactGroupStatus {
+    fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
+        match *self {
+            ContactGroupStatus::Active => xml.write("ACTIVE"),
+            ContactGroupStatus::Deleted => xml.write("DELETED"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContactGroupParams {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ContactGroupStatus>,
+}
+
+impl XmlSerializable for ContactGroupParams {
+    fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
+        xml.element_opt("Name", &self.name)?;
+        xml.element_opt("Status", &self.status)
+    }
+}
+
+impl ContactGroupParams {
+    pub fn new<S: Into<String>>(name: S) -> ContactGroupParams {
+        ContactGroupParams{name: Some(name.into()), status: None}
+    }
+}
+
+/// A group of contacts, e.g. for sending statements or segmenting customers.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContactGroup {
+    #[serde(rename = "ContactGroupID")]
+    pub contact_group_id: ContactGroupId,
+    pub name: String,
+    pub status: ContactGroupStatus,
+    /// The group's members, which Xero only returns when getting a single group.
+    #[serde(default)]
+    pub contacts: Vec<ContactSummary>,
+}
+
+impl ContactGroup {
+    pub fn get(client: &Client, id: ContactGroupId) -> Result<ContactGroup, Error> {
+        let groups: ContactGroups = client.get(&format!("/ContactGroups/{}", id))?;
+        groups.contact_groups.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    pub fn put(client: &Client, params: ContactGroupParams, idempotency_key: Option<&str>) -> Result<ContactGroup, Error> {
+        let mut body = Vec::new();
+        {
+            let mut xml = XmlWriter::new(&mut body);
+            xml.element("ContactGroup", &params)?;
+        }
+        let groups: ContactGroups = client.put("/ContactGroups", body.as_slice(), idempotency_key)?;
+        groups.contact_groups.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    pub fn update(client: &Client, id: ContactGroupId, params: ContactGroupParams, idempotency_key: Option<&str>) -> Result<ContactGroup, Error> {
+        let mut body = Vec::new();
+        {
+            let mut xml = XmlWriter::new(&mut body);
+            xml.element("ContactGroup", &params)?;
+        }
+        let groups: ContactGroups = client.post(&format!("/ContactGroups/{}", id), body.as_slice(), idempotency_key)?;
+        groups.contact_groups.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    pub fn rename<S: Into<String>>(client: &Client, id: ContactGroupId, name: S, idempotency_key: Option<&str>) -> Result<ContactGroup, Error> {
+        ContactGroup::update(client, id, ContactGroupParams::new(name), idempotency_key)
+    }
+
+    /// Deletes a group by updating its status; its contacts aren't changed.
+    pub fn delete(client: &Client, id: ContactGroupId, idempotency_key: Option<&str>) -> Result<ContactGroup, Error> {
+        let params = ContactGroupParams{name: None, status: Some(ContactGroupStatus::Deleted)};
+        ContactGroup::update(client, id, params, idempotency_key)
+    }
+
+    /// Adds contacts to a group, returning the contacts which were added.
+    pub fn add_contacts(client: &Client, id: ContactGroupId, contact_ids: Vec<ContactId>, idempotency_key: Option<&str>) -> Result<Vec<ContactSummary>, Error> {
+        let contacts: Vec<ContactIdParams> = contact_ids.into_iter().map(ContactIdParams::from).collect();
+        let mut body = Vec::new();
+        {
+            let mut xml = XmlWriter::new(&mut body);
+            xml.array("Contacts", "Contact", &contacts)?;
+        }
+        let members: GroupMembers = client.put(&format!("/ContactGroups/{}/Contacts", id), body.as_slice(), idempotency_key)?;
+        Ok(members.contacts)
+    }
+
+    pub fn remove_contact(client: &Client, id: ContactGroupId, contact_id: ContactId, idempotency_key: Option<&str>) -> Result<(), Error> {
+        client.delete(&format!("/ContactGroups/{}/Contacts/{}", id, contact_id), idempotency_key)
+    }
+
+    pub fn remove_all_contacts(client: &Client, id: ContactGroupId, idempotency_key: Option<&str>) -> Result<(), Error> {
+        client.delete(&format!("/ContactGroups/{}/Contacts", id), idempotency_key)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct GroupMembers {
+    contacts: Vec<ContactSummary>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContactGroups {
+    pub contact_groups: Vec<ContactGroup>,
+}
+
+impl ContactGroups {
+    pub fn get(client: &Client) -> Result<ContactGroups, Error> {
+        client.get("/ContactGroups")
+    }
+}
@@ -0,0 +1,185 @@
+use client::Client;
+use encoding::XmlWriter;
+use error::Error;
+use resources::contacts::{Contact, ContactIdParams, ContactStatus};
+use resources::ids::{ContactId, InvoiceId};
+use resources::invoices::{InvoiceStatus, Invoices};
+use std::collections::{HashMap, VecDeque};
+use std::collections::hash_map::Entry;
+
+/// The statuses of invoices which are moved to the contact being kept.
+pub const OPEN_INVOICE_STATUSES: &[InvoiceStatus] = &[
+    InvoiceStatus::Draft,
+    InvoiceStatus::Submitted,
+    InvoiceStatus::Authorised,
+];
+
+/// Why a contact was matched with another contact in its set of duplicates.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DuplicateReason {
+    EmailAddress,
+    TaxNumber,
+    Name,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Duplicate {
+    pub contact_id: ContactId,
+    pub name: String,
+    /// The contact this one was matched with: the contact being kept, or another duplicate.
+    pub matched_with: ContactId,
+    pub reason: DuplicateReason,
+}
+
+/// Merges duplicate contacts into the contact being kept.
+///
+/// Xero can't merge contacts through the API, so each duplicate's open invoices are moved to
+/// the kept contact and then the duplicate is archived. Paid and voided invoices stay with the
+/// archived duplicate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MergePlan {
+    pub keep: ContactId,
+    pub name: String,
+    pub duplicates: Vec<Duplicate>,
+}
+
+impl MergePlan {
+    /// Carries out the plan, returning the invoices which were moved.
+    ///
+    /// A duplicate is only archived once all of its open invoices have been moved, so the plan
+    /// can safely be executed again after an error. Its invoices are always read from Xero, not
+    /// a cache, so that none created since are left behind.
+    pub fn execute(&self, client: &Client) -> Result<Vec<InvoiceId>, Error> {
+        let mut moved = Vec::new();
+        for duplicate in &self.duplicates {
+            let path = Invoices::contact_path(duplicate.contact_id, OPEN_INVOICE_STATUSES);
+            let invoices: Invoices = client.get_uncached(&path)?;
+            for invoice in invoices.invoices {
+                move_invoice(client, invoice.invoice_id, self.keep)?;
+                moved.push(invoice.invoice_id);
+            }
+            Contact::archive(client, duplicate.contact_id, None)?;
+        }
+        Ok(moved)
+    }
+}
+
+fn move_invoice(client: &Client, id: InvoiceId, contact_id: ContactId) -> Result<(), Error> {
+    let mut body = Vec::new();
+    {
+        let mut xml = XmlWriter::new(&mut body);
+        xml.start_element("Invoice")?;
+        xml.element("Contact", &ContactIdParams::from(contact_id))?;
+        xml.end_element()?;
+    }
+    let _: Invoices = client.post(&format!("/Invoices/{}", id), body.as_slice(), None)?;
+    Ok(())
+}
+
+/// Finds active contacts with the same email address, tax number or name, ignoring case,
+/// spacing and punctuation.
+///
+/// Each set of duplicates gets one plan, which keeps whichever contact comes first in
+/// `contacts`. A contact can join a set through another duplicate, e.g. by sharing an email
+/// address with the kept contact and a tax number with a third contact; each duplicate's reason
+/// is the key it shares with the contact it was matched with, nearest the kept contact first.
+pub fn find_duplicates(contacts: &[Contact]) -> Vec<MergePlan> {
+    let active: Vec<&Contact> = contacts.iter()
+        .filter(|contact| contact.contact_status == ContactStatus::Active)
+        .collect();
+
+    // Contacts which share a key are joined into one set, whose root is its earliest contact.
+    let mut parent: Vec<usize> = (0..active.len()).collect();
+    let mut matches: Vec<Vec<(usize, DuplicateReason)>> = vec![Vec::new(); active.len()];
+    let mut first: HashMap<(DuplicateReason, String), usize> = HashMap::new();
+    for (i, contact) in active.iter().enumerate() {
+        for (reason, key) in keys(contact) {
+            if key.is_empty() {
+                continue;
+            }
+            match first.entry((reason, key)) {
+                Entry::Occupied(entry) => {
+                    let other = *entry.get();
+                    matches[i].push((other, reason));
+                    matches[other].push((i, reason));
+                    let root = find_root(&parent, other);
+                    let own = find_root(&parent, i);
+                    if own != root {
+                        parent[own.max(root)] = own.min(root);
+                    }
+                }
+                Entry::Vacant(entry) => {
+                    entry.insert(i);
+                }
+            }
+        }
+    }
+
+    // Each duplicate is reached from the kept contact through the matches which joined its set.
+    let mut matched: Vec<Option<(usize, DuplicateReason)>> = vec![None; active.len()];
+    for root in 0..active.len() {
+        if find_root(&parent, root) != root {
+            continue;
+        }
+        let mut queue = VecDeque::new();
+        queue.push_back(root);
+        while let Some(i) = queue.pop_front() {
+            for &(other, reason) in &matches[i] {
+                if other != root && matched[other].is_none() {
+                    matched[other] = Some((i, reason));
+                    queue.push_back(other);
+                }
+            }
+        }
+    }
+
+    let mut plans: Vec<MergePlan> = Vec::new();
+    let mut plan_of_root: HashMap<usize, usize> = HashMap::new();
+    for i in 0..active.len() {
+        let (other, reason) = match matched[i] {
+            Some(matched) => matched,
+            None => continue,
+        };
+        let duplicate = Duplicate{
+            contact_id: active[i].contact_id,
+            name: active[i].name.clone(),
+            matched_with: active[other].contact_id,
+            reason: reason,
+        };
+        let root = find_root(&parent, i);
+        match plan_of_root.entry(root) {
+            Entry::Occupied(entry) => plans[*entry.get()].duplicates.push(duplicate),
+            Entry::Vacant(entry) => {
+                entry.insert(plans.len());
+                plans.push(MergePlan{
+                    keep: active[root].contact_id,
+                    name: active[root].name.clone(),
+                    duplicates: vec![duplicate],
+                });
+            }
+        }
+    }
+    plans
+}
+
+fn find_root(parent: &[usize], mut i: usize) -> usize {
+    while parent[i] != i {
+        i = parent[i];
+    }
+    i
+}
+
+/// The normalised keys a contact is matched on, strongest first.
+fn keys(contact: &Contact) -> Vec<(DuplicateReason, String)> {
+    vec![
+        (DuplicateReason::EmailAddress, contact.email_address.trim().to_lowercase()),
+        (DuplicateReason::TaxNumber, normalise(&contact.tax_number)),
+        (DuplicateReason::Name, normalise(&contact.name)),
+    ]
+}
+
+/// Lowercases `s` and keeps only its letters and digits, e.g. `"A.B.C. Limited"` becomes
+/// `"abclimited"`.
+fn normalise(s: &str) -> String {
+    s.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+}
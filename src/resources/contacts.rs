@@ -1,6 +1,14 @@
+use bigdecimal::BigDecimal;
+use chrono::{DateTime, Utc};
 use client::Client;
-use encoding::{XmlError, XmlSerializable, XmlWriter};
+use encoding::{self, XmlError, XmlSerializable, XmlWriter};
 use error::Error;
+use resources::bulk::{self, BulkParams, BulkResults};
+use resources::contact_groups::{ContactGroup, ContactGroupStatus, ContactGroups};
+use resources::contact_merge::{self, MergePlan};
+use resources::history::{HistoryRecord, HistoryRecords};
+use resources::ids::{BrandingThemeId, ContactId};
+use tax_numbers::TaxRegion;
 
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -37,7 +45,7 @@ impl XmlSerializable for AddressType {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Address {
     pub address_type: AddressType,
@@ -107,7 +115,7 @@ impl XmlSerializable for PhoneType {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Phone {
     pub phone_type: PhoneType,
@@ -128,50 +136,214 @@ impl<'a> XmlSerializable for Phone {
     }
 }
 
-#[derive(Default, Serialize)]
-pub struct ContactIdParams<'a> {
+/// A person at a contact, in addition to the contact's primary person.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContactPerson {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub first_name: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub last_name: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub email_address: String,
+    /// Whether the person is copied into emails, e.g. of invoices, sent to the contact.
+    #[serde(default)]
+    pub include_in_emails: bool,
+}
+
+impl XmlSerializable for ContactPerson {
+    fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
+        if !self.first_name.is_empty() { xml.element("FirstName", &self.first_name)?; }
+        if !self.last_name.is_empty() { xml.element("LastName", &self.last_name)?; }
+        if !self.email_address.is_empty() { xml.element("EmailAddress", &self.email_address)?; }
+        xml.element("IncludeInEmails", &self.include_in_emails)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub enum PaymentTermType {
+    /// The given number of days after the invoice date.
+    #[serde(rename = "DAYSAFTERBILLDATE")]
+    DaysAfterBillDate,
+    /// The given number of days after the end of the invoice month.
+    #[serde(rename = "DAYSAFTERBILLMONTH")]
+    DaysAfterBillMonth,
+    /// The given day of the invoice month.
+    #[serde(rename = "OFCURRENTMONTH")]
+    OfCurrentMonth,
+    /// The given day of the month after the invoice month.
+    #[serde(rename = "OFFOLLOWINGMONTH")]
+    OfFollowingMonth,
+}
+
+impl XmlSerializable for PaymentTermType {
+    fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
+        match *self {
+            PaymentTermType::DaysAfterBillDate => xml.write("DAYSAFTERBILLDATE"),
+            PaymentTermType::DaysAfterBillMonth => xml.write("DAYSAFTERBILLMONTH"),
+            PaymentTermType::OfCurrentMonth => xml.write("OFCURRENTMONTH"),
+            PaymentTermType::OfFollowingMonth => xml.write("OFFOLLOWINGMONTH"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct PaymentTerm {
+    pub day: u32,
+    #[serde(rename = "Type")]
+    pub term_type: PaymentTermType,
+}
+
+impl XmlSerializable for PaymentTerm {
+    fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
+        xml.element("Day", &self.day)?;
+        xml.element("Type", &self.term_type)
+    }
+}
+
+/// When a contact's bills and sales invoices are due, if different from the organisation's default.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct PaymentTerms {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bills: Option<PaymentTerm>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sales: Option<PaymentTerm>,
+}
+
+impl XmlSerializable for PaymentTerms {
+    fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
+        xml.element_opt("Bills", &self.bills)?;
+        xml.element_opt("Sales", &self.sales)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Balance {
+    pub outstanding: BigDecimal,
+    pub overdue: BigDecimal,
+}
+
+/// The amounts a contact owes, and is owed, in the organisation's base currency.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Balances {
+    pub accounts_receivable: Option<Balance>,
+    pub accounts_payable: Option<Balance>,
+}
+
+/// A tracking option used by default for a contact's sales or purchases.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContactTrackingCategory {
+    pub tracking_category_name: String,
+    pub tracking_option_name: String,
+}
+
+impl XmlSerializable for ContactTrackingCategory {
+    fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
+        xml.element("TrackingCategoryName", &self.tracking_category_name)?;
+        xml.element("TrackingOptionName", &self.tracking_option_name)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct BrandingTheme {
+    #[serde(rename = "BrandingThemeID")]
+    pub branding_theme_id: BrandingThemeId,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub name: String,
+}
+
+impl From<BrandingThemeId> for BrandingTheme {
+    fn from(branding_theme_id: BrandingThemeId) -> BrandingTheme {
+        BrandingTheme{branding_theme_id: branding_theme_id, name: String::new()}
+    }
+}
+
+impl XmlSerializable for BrandingTheme {
+    fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
+        xml.element("BrandingThemeID", &self.branding_theme_id)
+    }
+}
+
+/// The bank details used when the contact is paid in a batch payment.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct BatchPayments {
+    #[serde(default)]
+    pub bank_account_number: String,
+    #[serde(default)]
+    pub bank_account_name: String,
+    #[serde(default)]
+    pub details: String,
+    #[serde(default)]
+    pub code: String,
+    #[serde(default)]
+    pub reference: String,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct ContactIdParams {
     #[serde(rename = "ContactID")]
-    pub contact_id: &'a str, // Required
+    pub contact_id: ContactId, // Required
+}
+
+impl From<ContactId> for ContactIdParams {
+    fn from(contact_id: ContactId) -> ContactIdParams {
+        ContactIdParams{contact_id: contact_id}
+    }
 }
 
-impl<'a> XmlSerializable for ContactIdParams<'a> {
+impl XmlSerializable for ContactIdParams {
     fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
         xml.element("ContactID", &self.contact_id)
     }
 }
 
-/// ... Some fields missing ...
-#[derive(Default, Serialize)]
+/// The writable fields of a contact.
+///
+/// `Balances`, `ContactGroups`, `BatchPayments`, `Discount`, `Website`, `HasAttachments` and
+/// `UpdatedDateUTC` can't be written, so are only on `Contact`; a contact's groups are changed
+/// through the contact group.
+#[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "PascalCase")]
-pub struct ContactParams<'a> {
+pub struct ContactParams {
     #[serde(rename = "ContactID")]
-    pub contact_id: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub contact_number: Option<&'a str>,
+    pub contact_id: Option<ContactId>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub account_number: Option<&'a str>,
+    pub contact_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_number: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub contact_status: Option<ContactStatus>,
+    pub name: String, // Required
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<&'a str>,
+    pub first_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub first_name: Option<&'a str>,
+    pub last_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub last_name: Option<&'a str>,
+    pub email_address: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub email_address: Option<&'a str>,
+    pub skype_user_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub skype_user_name: Option<&'a str>,
-    // #[serde(skip_serializing_if = "Option::is_none")]
-    // pub contact_persons: Option<Vec<ContactPerson>>,
+    pub contact_persons: Option<Vec<ContactPerson>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub bank_account_details: Option<&'a str>,
+    pub bank_account_details: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tax_number: Option<&'a str>,
+    pub tax_number: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub accounts_receivable_tax_type: Option<&'a str>,
+    pub accounts_receivable_tax_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub accounts_payable_tax_type: Option<&'a str>,
+    pub accounts_payable_tax_type: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub addresses: Option<Vec<Address>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -180,22 +352,41 @@ pub struct ContactParams<'a> {
     pub is_supplier: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_customer: Option<bool>,
-    // pub default_currency: Option<_>,
-    // pub xero_network_key: Option<_>,
-    // ...
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_currency: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub xero_network_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sales_default_account_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purchases_default_account_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sales_tracking_categories: Option<Vec<ContactTrackingCategory>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purchases_tracking_categories: Option<Vec<ContactTrackingCategory>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_terms: Option<PaymentTerms>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branding_theme: Option<BrandingTheme>,
+    /// The region `tax_number` is validated for before the contact is sent; not sent to Xero.
+    #[serde(skip)]
+    pub tax_region: Option<TaxRegion>,
 }
 
-impl<'a> XmlSerializable for ContactParams<'a> {
+impl XmlSerializable for ContactParams {
     fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
         xml.element_opt("ContactID", &self.contact_id)?;
         xml.element_opt("ContactNumber", &self.contact_number)?;
         xml.element_opt("AccountNumber", &self.account_number)?;
         xml.element_opt("ContactStatus", &self.contact_status)?;
-        xml.element_opt("Name", &self.name)?;
+        xml.element("Name", &self.name)?;
         xml.element_opt("FirstName", &self.first_name)?;
         xml.element_opt("LastName", &self.last_name)?;
         xml.element_opt("EmailAddress", &self.email_address)?;
         xml.element_opt("SkypeUserName", &self.skype_user_name)?;
+        if let Some(ref contact_persons) = self.contact_persons {
+            xml.array("ContactPersons", "ContactPerson", &contact_persons)?;
+        }
         xml.element_opt("BankAccountDetails", &self.bank_account_details)?;
         xml.element_opt("TaxNumber", &self.tax_number)?;
         xml.element_opt("AccountsReceivableTaxType", &self.accounts_receivable_tax_type)?;
@@ -207,24 +398,214 @@ impl<'a> XmlSerializable for ContactParams<'a> {
             xml.array("Phones", "Phone", &phones)?;
         }
         xml.element_opt("IsSupplier", &self.is_supplier)?;
-        xml.element_opt("IsCustomer", &self.is_customer)
+        xml.element_opt("IsCustomer", &self.is_customer)?;
+        xml.element_opt("DefaultCurrency", &self.default_currency)?;
+        xml.element_opt("XeroNetworkKey", &self.xero_network_key)?;
+        xml.element_opt("SalesDefaultAccountCode", &self.sales_default_account_code)?;
+        xml.element_opt("PurchasesDefaultAccountCode", &self.purchases_default_account_code)?;
+        if let Some(ref categories) = self.sales_tracking_categories {
+            xml.array("SalesTrackingCategories", "SalesTrackingCategory", &categories)?;
+        }
+        if let Some(ref categories) = self.purchases_tracking_categories {
+            xml.array("PurchasesTrackingCategories", "PurchasesTrackingCategory", &categories)?;
+        }
+        xml.element_opt("PaymentTerms", &self.payment_terms)?;
+        xml.element_opt("BrandingTheme", &self.branding_theme)
+    }
+}
+
+impl ContactParams {
+    /// Starts building the params for a new contact; Xero requires every contact to have a name.
+    pub fn builder<S: Into<String>>(name: S) -> ContactParamsBuilder {
+        ContactParamsBuilder{params: ContactParams{
+            contact_id: None,
+            contact_number: None,
+            account_number: None,
+            contact_status: None,
+            name: name.into(),
+            first_name: None,
+            last_name: None,
+            email_address: None,
+            skype_user_name: None,
+            contact_persons: None,
+            bank_account_details: None,
+            tax_number: None,
+            accounts_receivable_tax_type: None,
+            accounts_payable_tax_type: None,
+            addresses: None,
+            phones: None,
+            is_supplier: None,
+            is_customer: None,
+            default_currency: None,
+            xero_network_key: None,
+            sales_default_account_code: None,
+            purchases_default_account_code: None,
+            sales_tracking_categories: None,
+            purchases_tracking_categories: None,
+            payment_terms: None,
+            branding_theme: None,
+            tax_region: None,
+        }}
     }
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug)]
+pub struct ContactParamsBuilder {
+    params: ContactParams,
+}
+
+impl ContactParamsBuilder {
+    pub fn contact_number<S: Into<String>>(mut self, contact_number: S) -> Self {
+        self.params.contact_number = Some(contact_number.into());
+        self
+    }
+
+    pub fn account_number<S: Into<String>>(mut self, account_number: S) -> Self {
+        self.params.account_number = Some(account_number.into());
+        self
+    }
+
+    pub fn contact_status(mut self, contact_status: ContactStatus) -> Self {
+        self.params.contact_status = Some(contact_status);
+        self
+    }
+
+    pub fn first_name<S: Into<String>>(mut self, first_name: S) -> Self {
+        self.params.first_name = Some(first_name.into());
+        self
+    }
+
+    pub fn last_name<S: Into<String>>(mut self, last_name: S) -> Self {
+        self.params.last_name = Some(last_name.into());
+        self
+    }
+
+    pub fn email_address<S: Into<String>>(mut self, email_address: S) -> Self {
+        self.params.email_address = Some(email_address.into());
+        self
+    }
+
+    pub fn skype_user_name<S: Into<String>>(mut self, skype_user_name: S) -> Self {
+        self.params.skype_user_name = Some(skype_user_name.into());
+        self
+    }
+
+    pub fn contact_person(mut self, contact_person: ContactPerson) -> Self {
+        self.params.contact_persons.get_or_insert_with(Vec::new).push(contact_person);
+        self
+    }
+
+    pub fn bank_account_details<S: Into<String>>(mut self, bank_account_details: S) -> Self {
+        self.params.bank_account_details = Some(bank_account_details.into());
+        self
+    }
+
+    pub fn tax_number<S: Into<String>>(mut self, tax_number: S) -> Self {
+        self.params.tax_number = Some(tax_number.into());
+        self
+    }
+
+    /// Validates the tax number for `region` before the contact is sent, rather than leaving
+    /// Xero to reject it.
+    pub fn tax_region(mut self, region: TaxRegion) -> Self {
+        self.params.tax_region = Some(region);
+        self
+    }
+
+    pub fn accounts_receivable_tax_type<S: Into<String>>(mut self, tax_type: S) -> Self {
+        self.params.accounts_receivable_tax_type = Some(tax_type.into());
+        self
+    }
+
+    pub fn accounts_payable_tax_type<S: Into<String>>(mut self, tax_type: S) -> Self {
+        self.params.accounts_payable_tax_type = Some(tax_type.into());
+        self
+    }
+
+    pub fn address(mut self, address: Address) -> Self {
+        self.params.addresses.get_or_insert_with(Vec::new).push(address);
+        self
+    }
+
+    pub fn phone(mut self, phone: Phone) -> Self {
+        self.params.phones.get_or_insert_with(Vec::new).push(phone);
+        self
+    }
+
+    pub fn is_supplier(mut self, is_supplier: bool) -> Self {
+        self.params.is_supplier = Some(is_supplier);
+        self
+    }
+
+    pub fn is_customer(mut self, is_customer: bool) -> Self {
+        self.params.is_customer = Some(is_customer);
+        self
+    }
+
+    /// The contact's currency, e.g. `"USD"`.
+    pub fn default_currency<S: Into<String>>(mut self, currency: S) -> Self {
+        self.params.default_currency = Some(currency.into());
+        self
+    }
+
+    pub fn xero_network_key<S: Into<String>>(mut self, xero_network_key: S) -> Self {
+        self.params.xero_network_key = Some(xero_network_key.into());
+        self
+    }
+
+    pub fn sales_default_account_code<S: Into<String>>(mut self, account_code: S) -> Self {
+        self.params.sales_default_account_code = Some(account_code.into());
+        self
+    }
+
+    pub fn purchases_default_account_code<S: Into<String>>(mut self, account_code: S) -> Self {
+        self.params.purchases_default_account_code = Some(account_code.into());
+        self
+    }
+
+    pub fn sales_tracking_category(mut self, category: ContactTrackingCategory) -> Self {
+        self.params.sales_tracking_categories.get_or_insert_with(Vec::new).push(category);
+        self
+    }
+
+    pub fn purchases_tracking_category(mut self, category: ContactTrackingCategory) -> Self {
+        self.params.purchases_tracking_categories.get_or_insert_with(Vec::new).push(category);
+        self
+    }
+
+    pub fn bills_payment_term(mut self, term: PaymentTerm) -> Self {
+        self.params.payment_terms.get_or_insert_with(PaymentTerms::default).bills = Some(term);
+        self
+    }
+
+    pub fn sales_payment_term(mut self, term: PaymentTerm) -> Self {
+        self.params.payment_terms.get_or_insert_with(PaymentTerms::default).sales = Some(term);
+        self
+    }
+
+    pub fn branding_theme(mut self, branding_theme_id: BrandingThemeId) -> Self {
+        self.params.branding_theme = Some(BrandingTheme::from(branding_theme_id));
+        self
+    }
+
+    pub fn build(self) -> ContactParams {
+        self.params
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct ContactSummary {
     #[serde(rename = "ContactID")]
-    pub contact_id: String,
+    pub contact_id: ContactId,
     pub name: String,
 }
 
-/// ... Some fields missing ...
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Contact {
     #[serde(rename = "ContactID")]
-    pub contact_id: String,
+    pub contact_id: ContactId,
     pub contact_status: ContactStatus,
     pub name: String,
     #[serde(default)]
@@ -236,6 +617,8 @@ pub struct Contact {
     #[serde(default)]
     pub skype_user_name: String,
     #[serde(default)]
+    pub contact_persons: Vec<ContactPerson>,
+    #[serde(default)]
     pub bank_account_details: String,
     #[serde(default)]
     pub tax_number: String,
@@ -245,23 +628,110 @@ pub struct Contact {
     pub phones: Vec<Phone>,
     pub is_supplier: bool,
     pub is_customer: bool,
-    // pub default_currency
-    // pub updated_date_utc
-    // ...
+    pub default_currency: Option<String>,
+    pub xero_network_key: Option<String>,
+    pub sales_default_account_code: Option<String>,
+    pub purchases_default_account_code: Option<String>,
+    #[serde(default)]
+    pub sales_tracking_categories: Vec<ContactTrackingCategory>,
+    #[serde(default)]
+    pub purchases_tracking_categories: Vec<ContactTrackingCategory>,
+    pub payment_terms: Option<PaymentTerms>,
+    /// Only returned by Xero when getting a single contact, or by `Contacts::get_with_groups`.
+    #[serde(default)]
+    pub contact_groups: Vec<ContactGroup>,
+    pub branding_theme: Option<BrandingTheme>,
+    pub batch_payments: Option<BatchPayments>,
+    /// The default discount rate, as a percentage, for the contact's invoices.
+    pub discount: Option<BigDecimal>,
+    pub website: Option<String>,
+    pub balances: Option<Balances>,
+    #[serde(default)]
+    pub has_attachments: bool,
+    #[serde(rename = "UpdatedDateUTC")]
+    #[serde(default, deserialize_with = "encoding::deserialize_json_date_opt")]
+    pub updated_date_utc: Option<DateTime<Utc>>,
 }
 
 impl Contact {
-    pub fn put(client: &Client, params: ContactParams) -> Result<Contact, Error> {
+    pub fn get(client: &Client, id: ContactId) -> Result<Contact, Error> {
+        let contacts: Contacts = client.get(&format!("/Contacts/{}", id))?;
+        contacts.contacts.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    pub fn put(client: &Client, params: ContactParams, idempotency_key: Option<&str>) -> Result<Contact, Error> {
+        params.validate()?;
         let mut body = Vec::new();
         {
             let mut xml = XmlWriter::new(&mut body);
             xml.element("Contact", &params)?;
         }
-        let contacts: Contacts = client.put("/Contacts", body.as_slice())?;
-        Ok(contacts.contacts.into_iter().next().expect("Expected contact after successful PUT"))
+        let contacts: Contacts = client.put("/Contacts", body.as_slice(), idempotency_key)?;
+        contacts.contacts.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    pub fn update(client: &Client, id: ContactId, params: ContactParams, idempotency_key: Option<&str>) -> Result<Contact, Error> {
+        params.validate()?;
+        let mut body = Vec::new();
+        {
+            let mut xml = XmlWriter::new(&mut body);
+            xml.element("Contact", &params)?;
+        }
+        let contacts: Contacts = client.post(&format!("/Contacts/{}", id), body.as_slice(), idempotency_key)?;
+        contacts.contacts.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    /// Archives a contact, hiding it from lists in Xero. Contacts can't be deleted.
+    pub fn archive(client: &Client, id: ContactId, idempotency_key: Option<&str>) -> Result<Contact, Error> {
+        Contact::set_status(client, id, ContactStatus::Archived, idempotency_key)
+    }
+
+    /// Makes an archived contact active again.
+    pub fn restore(client: &Client, id: ContactId, idempotency_key: Option<&str>) -> Result<Contact, Error> {
+        Contact::set_status(client, id, ContactStatus::Active, idempotency_key)
+    }
+
+    fn set_status(client: &Client, id: ContactId, status: ContactStatus, idempotency_key: Option<&str>) -> Result<Contact, Error> {
+        let mut body = Vec::new();
+        {
+            let mut xml = XmlWriter::new(&mut body);
+            xml.start_element("Contact")?;
+            xml.element("ContactStatus", &status)?;
+            xml.end_element()?;
+        }
+        let contacts: Contacts = client.post(&format!("/Contacts/{}", id), body.as_slice(), idempotency_key)?;
+        contacts.contacts.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    /// Whether a contact is a subcontractor under the UK Construction Industry Scheme, and the
+    /// rate deducted from their payments if so.
+    pub fn cis_settings(client: &Client, id: ContactId) -> Result<CisSettings, Error> {
+        let settings: ContactCisSettings = client.get(&format!("/Contacts/{}/CISSettings", id))?;
+        settings.cis_settings.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    /// The changes made to a contact, e.g. when it was created, edited or archived.
+    pub fn history(client: &Client, id: ContactId) -> Result<Vec<HistoryRecord>, Error> {
+        let records: HistoryRecords = client.get(&format!("/Contacts/{}/History", id))?;
+        Ok(records.history_records)
     }
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct CisSettings {
+    #[serde(rename = "CISEnabled")]
+    pub cis_enabled: bool,
+    /// The percentage deducted, only returned when CIS is enabled.
+    #[serde(rename = "Rate")]
+    pub rate: Option<BigDecimal>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContactCisSettings {
+    #[serde(rename = "CISSettings")]
+    cis_settings: Vec<CisSettings>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Contacts {
@@ -273,12 +743,59 @@ impl Contacts {
         client.get("/Contacts")
     }
 
-    pub fn put(client: &Client, params: Vec<ContactParams>) -> Result<Contacts, Error> {
+    /// Gets every contact along with the active groups it belongs to, which Xero leaves out of
+    /// lists of contacts. Each group's members are fetched with a request of their own.
+    pub fn get_with_groups(client: &Client) -> Result<Contacts, Error> {
+        let mut contacts = Contacts::get(client)?;
+        let groups = ContactGroups::get(client)?;
+        for group in groups.contact_groups.into_iter().filter(|group| group.status == ContactGroupStatus::Active) {
+            let group = ContactGroup::get(client, group.contact_group_id)?;
+            for contact in contacts.contacts.iter_mut() {
+                let member = group.contacts.iter().any(|member| member.contact_id == contact.contact_id);
+                let listed = contact.contact_groups.iter().any(|g| g.contact_group_id == group.contact_group_id);
+                if member && !listed {
+                    contact.contact_groups.push(ContactGroup{contacts: Vec::new(), ..group.clone()});
+                }
+            }
+        }
+        Ok(contacts)
+    }
+
+    /// Plans how to merge each set of active contacts which look like duplicates of each other.
+    pub fn find_duplicates(&self) -> Vec<MergePlan> {
+        contact_merge::find_duplicates(&self.contacts)
+    }
+
+    pub fn put(client: &Client, params: Vec<ContactParams>, idempotency_key: Option<&str>) -> Result<Contacts, Error> {
+        for params in &params {
+            params.validate()?;
+        }
         let mut body = Vec::new();
         {
             let mut xml = XmlWriter::new(&mut body);
             xml.array("Contacts", "Contact", &params)?;
         }
-        client.put("/Contacts", body.as_slice())
+        client.put("/Contacts", body.as_slice(), idempotency_key)
+    }
+
+    /// Creates each contact independently, so that one invalid element doesn't fail the others.
+    pub fn put_each(client: &Client, params: Vec<ContactParams>, idempotency_key: Option<&str>) -> Result<BulkResults<Contact>, Error> {
+        bulk::put_each(client, &params, idempotency_key)
+    }
+}
+
+impl BulkParams for ContactParams {
+    type Resource = Contact;
+
+    fn endpoint() -> &'static str { "/Contacts" }
+    fn collection() -> &'static str { "Contacts" }
+    fn element() -> &'static str { "Contact" }
+
+    /// Checks the tax number is valid in `tax_region`, if both are set.
+    fn validate(&self) -> Result<(), Error> {
+        match (self.tax_region, self.tax_number.as_ref()) {
+            (Some(region), Some(tax_number)) if !tax_number.trim().is_empty() => Ok(region.validate(tax_number)?),
+            _ => Ok(()),
+        }
     }
 }
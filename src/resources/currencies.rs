@@ -0,0 +1,73 @@
+use bigdecimal::BigDecimal;
+use client::Client;
+use encoding::{XmlError, XmlSerializable, XmlWriter};
+use error::Error;
+use resources::invoice_totals;
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CurrencyParams {
+    /// The ISO 4217 code of the currency, e.g. `"USD"`.
+    pub code: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl XmlSerializable for CurrencyParams {
+    fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
+        xml.element("Code", &self.code)?;
+        xml.element_opt("Description", &self.description)
+    }
+}
+
+impl CurrencyParams {
+    pub fn new<S: Into<String>>(code: S) -> CurrencyParams {
+        CurrencyParams{code: code.into(), description: None}
+    }
+}
+
+/// A currency which the organisation can bill and pay in, including its base currency.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Currency {
+    pub code: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+impl Currency {
+    /// Enables a currency; this requires a Xero plan with multi-currency.
+    pub fn put(client: &Client, params: CurrencyParams, idempotency_key: Option<&str>) -> Result<Currency, Error> {
+        let mut body = Vec::new();
+        {
+            let mut xml = XmlWriter::new(&mut body);
+            xml.element("Currency", &params)?;
+        }
+        let currencies: Currencies = client.put("/Currencies", body.as_slice(), idempotency_key)?;
+        currencies.currencies.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Currencies {
+    pub currencies: Vec<Currency>,
+}
+
+impl Currencies {
+    pub fn get(client: &Client) -> Result<Currencies, Error> {
+        client.get("/Currencies")
+    }
+}
+
+/// Converts an amount to the base currency, rounded to 2 decimal places.
+///
+/// Xero's `CurrencyRate` is the number of units of the foreign currency per unit of the base
+/// currency, so a USD invoice in an AUD organisation with a rate of `0.75` has AUD 100 for USD 75.
+/// Returns `None` for a rate of zero, which can't be converted.
+pub fn to_base_currency(amount: &BigDecimal, currency_rate: &BigDecimal) -> Option<BigDecimal> {
+    if *currency_rate == BigDecimal::from(0) {
+        return None;
+    }
+    Some(invoice_totals::round(&(amount.clone() / currency_rate.clone()), 2))
+}
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use encoding;
+
+/// A change to a resource, as shown in its history and notes in Xero.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct HistoryRecord {
+    /// What happened, e.g. `Created`, `Edited` or `Archived`.
+    #[serde(default)]
+    pub changes: String,
+    #[serde(rename = "DateUTC")]
+    #[serde(default, deserialize_with = "encoding::deserialize_json_date_opt")]
+    pub date_utc: Option<DateTime<Utc>>,
+    /// The name of the user or application which made the change.
+    #[serde(default)]
+    pub user: String,
+    #[serde(default)]
+    pub details: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct HistoryRecords {
+    pub history_records: Vec<HistoryRecord>,
+}
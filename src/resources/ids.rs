@@ -0,0 +1,130 @@
+use encoding::{XmlError, XmlSerializable, XmlWriter};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de;
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// An error parsing a resource ID from a string that isn't a valid GUID.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseIdError {
+    kind: &'static str,
+    value: String,
+}
+
+impl fmt::Display for ParseIdError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid {}: {:?} is not a GUID", self.kind, self.value)
+    }
+}
+
+impl error::Error for ParseIdError {
+    fn description(&self) -> &str {
+        "invalid resource id"
+    }
+}
+
+macro_rules! guid_id {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+        pub struct $name(Uuid);
+
+        impl $name {
+            pub fn new(uuid: Uuid) -> $name {
+                $name(uuid)
+            }
+
+            pub fn as_uuid(&self) -> &Uuid {
+                &self.0
+            }
+        }
+
+        impl From<Uuid> for $name {
+            fn from(uuid: Uuid) -> $name {
+                $name(uuid)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = ParseIdError;
+
+            fn from_str(s: &str) -> Result<$name, ParseIdError> {
+                Uuid::parse_str(s).map($name).map_err(|_| {
+                    ParseIdError{kind: stringify!($name), value: s.to_string()}
+                })
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.0.hyphenated())
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.to_string())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$name, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                s.parse().map_err(de::Error::custom)
+            }
+        }
+
+        impl XmlSerializable for $name {
+            fn write(&self, xml: &mut XmlWriter) -> Result<(), XmlError> {
+                xml.write(&self.to_string())
+            }
+        }
+    }
+}
+
+guid_id!(
+    /// The `ContactID` of a Xero contact.
+    ContactId
+);
+guid_id!(
+    /// The `InvoiceID` of a Xero invoice.
+    InvoiceId
+);
+guid_id!(
+    /// The `ItemID` of a Xero item.
+    ItemId
+);
+guid_id!(
+    /// The `PaymentID` of a Xero payment.
+    PaymentId
+);
+guid_id!(
+    /// The `CreditNoteID` of a Xero credit note.
+    CreditNoteId
+);
+guid_id!(
+    /// The `PrepaymentID` of a Xero prepayment.
+    PrepaymentId
+);
+guid_id!(
+    /// The `OverpaymentID` of a Xero overpayment.
+    OverpaymentId
+);
+guid_id!(
+    /// The `AccountID` of an account in the chart of accounts.
+    AccountId
+);
+guid_id!(
+    /// The `ContactGroupID` of a group of contacts.
+    ContactGroupId
+);
+guid_id!(
+    /// The `BrandingThemeID` of a branding theme for invoices and other documents.
+    BrandingThemeId
+);
+guid_id!(
+    /// The `LineItemID` of a line item on an invoice or other document.
+    LineItemId
+);
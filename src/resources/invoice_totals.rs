@@ -0,0 +1,212 @@
+use bigdecimal::BigDecimal;
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+use resources::invoices::{Invoice, InvoiceParams, LineAmountType, LineItemParams};
+
+/// Predicts the totals Xero will calculate for an invoice before it is sent.
+///
+/// Tax is calculated and rounded per line, as Xero does by default, so the
+/// totals are the sums of the rounded line amounts rather than rounded sums.
+#[derive(Clone, Debug)]
+pub struct InvoiceCalculator {
+    tax_rates: HashMap<String, BigDecimal>,
+    unit_decimal_places: i64,
+}
+
+impl Default for InvoiceCalculator {
+    fn default() -> Self {
+        InvoiceCalculator{tax_rates: HashMap::new(), unit_decimal_places: 2}
+    }
+}
+
+impl InvoiceCalculator {
+    pub fn new() -> InvoiceCalculator {
+        InvoiceCalculator::default()
+    }
+
+    /// Sets the rate, as a percentage, of a tax type (e.g. `OUTPUT2` at 15).
+    pub fn tax_rate<S: Into<String>>(mut self, tax_type: S, percent: BigDecimal) -> Self {
+        self.tax_rates.insert(tax_type.into(), percent);
+        self
+    }
+
+    /// Sets the decimal places unit amounts are rounded to, which is 2 unless
+    /// invoices are sent with `unitdp=4`.
+    pub fn unit_decimal_places(mut self, places: u32) -> Self {
+        self.unit_decimal_places = places as i64;
+        self
+    }
+
+    pub fn calculate(&self, invoice: &InvoiceParams) -> Result<InvoiceTotals, TotalsError> {
+        let line_amount_type = invoice.line_amount_types.unwrap_or(LineAmountType::Exclusive);
+        let mut totals = InvoiceTotals{
+            line_items: Vec::with_capacity(invoice.line_items.len()),
+            sub_total: zero(),
+            total_tax: zero(),
+            total: zero(),
+            total_discount: zero(),
+        };
+        for (index, line) in invoice.line_items.iter().enumerate() {
+            let line = self.calculate_line(index, line, line_amount_type)?;
+            totals.total_tax = totals.total_tax + line.tax_amount.clone();
+            totals.total_discount = totals.total_discount + line.discount.clone();
+            totals.total = totals.total + line.line_amount.clone();
+            totals.line_items.push(line);
+        }
+        if line_amount_type == LineAmountType::Inclusive {
+            totals.sub_total = totals.total.clone() - totals.total_tax.clone();
+        } else {
+            totals.sub_total = totals.total.clone();
+            totals.total = totals.sub_total.clone() + totals.total_tax.clone();
+        }
+        Ok(totals)
+    }
+
+    fn calculate_line(&self, index: usize, line: &LineItemParams, line_amount_type: LineAmountType) -> Result<LineTotals, TotalsError> {
+        let quantity = match line.quantity {
+            Some(quantity) => decimal(quantity).ok_or(TotalsError::InvalidNumber{line: index, field: "Quantity"})?,
+            None => BigDecimal::from(1),
+        };
+        let unit_amount = line.unit_amount.as_ref().map_or_else(zero, |amount| round(amount, self.unit_decimal_places));
+        let gross = round(&(round(&quantity, 4) * unit_amount), 2);
+        let line_amount = match (line.line_amount.as_ref(), line.discount_rate.as_ref(), line.discount_amount.as_ref()) {
+            (Some(amount), _, _) => round(amount, 2),
+            (None, Some(rate), _) => {
+                let remaining = (BigDecimal::from(100) - rate.clone()) / BigDecimal::from(100);
+                round(&(gross.clone() * remaining), 2)
+            }
+            (None, None, Some(discount)) => gross.clone() - round(discount, 2),
+            (None, None, None) => gross.clone(),
+        };
+        let discount = if line.line_amount.is_none() { gross - line_amount.clone() } else { zero() };
+
+        let tax_amount = match (line_amount_type, line.tax_amount.as_ref()) {
+            (LineAmountType::NoTax, _) => zero(),
+            (_, Some(tax)) => round(tax, 2),
+            (_, None) => {
+                let tax_type = line.tax_type.as_ref().ok_or(TotalsError::MissingTaxType{line: index})?;
+                let rate = self.tax_rates.get(tax_type)
+                    .ok_or_else(|| TotalsError::UnknownTaxType{line: index, tax_type: tax_type.clone()})?;
+                if line_amount_type == LineAmountType::Inclusive {
+                    let divisor = BigDecimal::from(100) + rate.clone();
+                    round(&(line_amount.clone() * rate.clone() / divisor), 2)
+                } else {
+                    round(&(line_amount.clone() * rate.clone() / BigDecimal::from(100)), 2)
+                }
+            }
+        };
+        Ok(LineTotals{line_amount: line_amount, tax_amount: tax_amount, discount: discount})
+    }
+}
+
+/// The amounts calculated for a single line item.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineTotals {
+    /// The `LineAmount`, after any discount, in the invoice's line amount type.
+    pub line_amount: BigDecimal,
+    pub tax_amount: BigDecimal,
+    pub discount: BigDecimal,
+}
+
+/// The totals Xero is expected to calculate for an invoice.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InvoiceTotals {
+    pub line_items: Vec<LineTotals>,
+    pub sub_total: BigDecimal,
+    pub total_tax: BigDecimal,
+    pub total: BigDecimal,
+    pub total_discount: BigDecimal,
+}
+
+impl InvoiceTotals {
+    /// Compares these totals with those of an invoice returned by Xero.
+    ///
+    /// Line items are compared by position, so the invoice should be the one
+    /// created from the calculated `InvoiceParams`.
+    pub fn mismatches(&self, invoice: &Invoice) -> Vec<TotalsMismatch> {
+        let mut mismatches = Vec::new();
+        {
+            let mut check = |field: String, expected: &BigDecimal, actual: Option<BigDecimal>| {
+                if let Some(actual) = actual {
+                    if round(&actual, 2) != *expected {
+                        mismatches.push(TotalsMismatch{field: field, expected: expected.clone(), actual: actual});
+                    }
+                }
+            };
+            check(String::from("SubTotal"), &self.sub_total, invoice.subtotal.clone());
+            check(String::from("TotalTax"), &self.total_tax, Some(invoice.total_tax.clone()));
+            check(String::from("Total"), &self.total, Some(invoice.total.clone()));
+            check(String::from("TotalDiscount"), &self.total_discount, invoice.total_discount.clone());
+            for (index, (expected, actual)) in self.line_items.iter().zip(invoice.line_items.iter()).enumerate() {
+                check(format!("LineItems[{}].LineAmount", index), &expected.line_amount, actual.line_amount.clone());
+                check(format!("LineItems[{}].TaxAmount", index), &expected.tax_amount, actual.tax_amount.clone());
+            }
+        }
+        mismatches
+    }
+
+    pub fn matches(&self, invoice: &Invoice) -> bool {
+        self.line_items.len() == invoice.line_items.len() && self.mismatches(invoice).is_empty()
+    }
+}
+
+/// An amount on a returned invoice which differs from the calculated one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TotalsMismatch {
+    /// The Xero name of the field, e.g. `TotalTax` or `LineItems[0].LineAmount`.
+    pub field: String,
+    pub expected: BigDecimal,
+    pub actual: BigDecimal,
+}
+
+/// A line item whose totals can't be calculated.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TotalsError {
+    /// The line has no `TaxType` or `TaxAmount`, so its tax depends on the account.
+    MissingTaxType{line: usize},
+    /// The line's `TaxType` has no rate in the calculator.
+    UnknownTaxType{line: usize, tax_type: String},
+    /// A quantity or amount isn't a finite number.
+    InvalidNumber{line: usize, field: &'static str},
+}
+
+impl fmt::Display for TotalsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TotalsError::MissingTaxType{line} => write!(f, "line item {} has no tax type or tax amount", line),
+            TotalsError::UnknownTaxType{line, ref tax_type} => write!(f, "line item {} has unknown tax type {:?}", line, tax_type),
+            TotalsError::InvalidNumber{line, field} => write!(f, "line item {} has an invalid {}", line, field),
+        }
+    }
+}
+
+impl error::Error for TotalsError {
+    fn description(&self) -> &str {
+        "invoice totals can't be calculated"
+    }
+}
+
+fn zero() -> BigDecimal {
+    BigDecimal::from(0).with_scale(2)
+}
+
+fn decimal(value: f64) -> Option<BigDecimal> {
+    if value.is_finite() { BigDecimal::from_str(&value.to_string()).ok() } else { None }
+}
+
+/// Rounds half away from zero, as Xero does, rather than truncating like `with_scale`.
+pub(crate) fn round(value: &BigDecimal, places: i64) -> BigDecimal {
+    let truncated = value.with_scale(places);
+    let remainder = (value.clone() - truncated.clone()) * BigDecimal::from(2);
+    let unit = BigDecimal::from(1) / BigDecimal::from(10i64.pow(places as u32));
+    if remainder >= unit {
+        truncated + unit
+    } else if -remainder >= unit {
+        truncated - unit
+    } else {
+        truncated
+    }
+}
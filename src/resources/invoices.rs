@@ -1,10 +1,16 @@
 use bigdecimal::BigDecimal;
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use client::Client;
-use encoding::{XmlError, XmlSerializable, XmlWriter};
-use error::Error;
+use encoding::{self, XmlError, XmlSerializable, XmlWriter};
+use error::{Error, RequestError};
+use std::error;
+use std::fmt;
+use std::io::Read;
 
+use resources::bulk::{self, BulkParams, BulkResults};
 use resources::contacts::{ContactIdParams, ContactSummary};
+use resources::currencies;
+use resources::ids::{BrandingThemeId, ContactId, CreditNoteId, InvoiceId, LineItemId, OverpaymentId, PaymentId, PrepaymentId};
 
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
 pub enum InvoiceType {
@@ -40,19 +46,38 @@ pub enum InvoiceStatus {
     Voided,
 }
 
-impl XmlSerializable for InvoiceStatus {
-    fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
+impl InvoiceStatus {
+    /// Whether Xero allows an invoice to be updated from this status to `to`.
+    ///
+    /// Invoices only become `PAID` when payments are applied, never by being updated.
+    pub fn can_transition_to(&self, to: InvoiceStatus) -> bool {
+        use self::InvoiceStatus::*;
+        match (*self, to) {
+            (Draft, Submitted) | (Draft, Authorised) | (Draft, Deleted) => true,
+            (Submitted, Draft) | (Submitted, Authorised) | (Submitted, Deleted) => true,
+            (Authorised, Voided) => true,
+            _ => false,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
         match *self {
-            InvoiceStatus::Draft => xml.write("DRAFT"),
-            InvoiceStatus::Submitted => xml.write("SUBMITTED"),
-            InvoiceStatus::Deleted => xml.write("DELETED"),
-            InvoiceStatus::Authorised => xml.write("AUTHORISED"),
-            InvoiceStatus::Paid => xml.write("PAID"),
-            InvoiceStatus::Voided => xml.write("VOIDED"),
+            InvoiceStatus::Draft => "DRAFT",
+            InvoiceStatus::Submitted => "SUBMITTED",
+            InvoiceStatus::Deleted => "DELETED",
+            InvoiceStatus::Authorised => "AUTHORISED",
+            InvoiceStatus::Paid => "PAID",
+            InvoiceStatus::Voided => "VOIDED",
         }
     }
 }
 
+impl XmlSerializable for InvoiceStatus {
+    fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
+        xml.write(self.as_str())
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "PascalCase")] // NOTE: Not SCREAMING like other enums
 pub enum LineAmountType {
@@ -71,29 +96,55 @@ impl XmlSerializable for LineAmountType {
     }
 }
 
+/// The option of a tracking category which a line item is assigned to.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct LineItemTracking {
+    /// The name of the tracking category, e.g. `Region`.
+    pub name: String,
+    /// The name of the option, e.g. `North`.
+    pub option: String,
+}
 
-/// ... Some fields missing ...
-#[derive(Serialize)]
+impl XmlSerializable for LineItemTracking {
+    fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
+        xml.element("Name", &self.name)?;
+        xml.element("Option", &self.option)
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "PascalCase")]
-pub struct LineItemParams<'a> {
-    pub description: &'a str, // Required
+pub struct LineItemParams {
+    /// Identifies an existing line item when updating an invoice; line items without one are added.
+    #[serde(rename = "LineItemID")]
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub item_code: Option<&'a str>,
+    pub line_item_id: Option<LineItemId>,
+    pub description: String, // Required
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub item_code: Option<String>,
     pub quantity: Option<f64>,
     pub unit_amount: Option<BigDecimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub line_amount: Option<BigDecimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tax_amount: Option<f64>,
+    pub tax_amount: Option<BigDecimal>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tax_type: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tracking: Vec<LineItemTracking>,
+    /// The discount as a percentage, e.g. `12.5`.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub account_code: Option<&'a str>,
+    pub discount_rate: Option<BigDecimal>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub discount_rate: Option<u32>,
-    // ...
+    pub discount_amount: Option<BigDecimal>,
 }
 
-impl<'a> XmlSerializable for LineItemParams<'a> {
+impl XmlSerializable for LineItemParams {
     fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
+        xml.element_opt("LineItemID", &self.line_item_id)?;
         xml.element("Description", &self.description)?;
         xml.element_opt("ItemCode", &self.item_code)?;
         xml.element_opt("Quantity", &self.quantity)?;
@@ -101,38 +152,138 @@ impl<'a> XmlSerializable for LineItemParams<'a> {
         xml.element_opt("LineAmount", &self.line_amount)?;
         xml.element_opt("TaxAmount", &self.tax_amount)?;
         xml.element_opt("AccountCode", &self.account_code)?;
-        xml.element_opt("DiscountRate", &self.discount_rate)
+        xml.element_opt("TaxType", &self.tax_type)?;
+        if !self.tracking.is_empty() {
+            xml.array("Tracking", "TrackingCategory", &self.tracking)?;
+        }
+        xml.element_opt("DiscountRate", &self.discount_rate)?;
+        xml.element_opt("DiscountAmount", &self.discount_amount)
+    }
+}
+
+impl LineItemParams {
+    pub fn builder<S: Into<String>>(description: S) -> LineItemParamsBuilder {
+        LineItemParamsBuilder{params: LineItemParams{
+            line_item_id: None,
+            description: description.into(),
+            item_code: None,
+            quantity: None,
+            unit_amount: None,
+            line_amount: None,
+            tax_amount: None,
+            account_code: None,
+            tax_type: None,
+            tracking: Vec::new(),
+            discount_rate: None,
+            discount_amount: None,
+        }}
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LineItemParamsBuilder {
+    params: LineItemParams,
+}
+
+impl LineItemParamsBuilder {
+    pub fn item_code<S: Into<String>>(mut self, item_code: S) -> Self {
+        self.params.item_code = Some(item_code.into());
+        self
+    }
+
+    pub fn quantity(mut self, quantity: f64) -> Self {
+        self.params.quantity = Some(quantity);
+        self
+    }
+
+    pub fn unit_amount(mut self, unit_amount: BigDecimal) -> Self {
+        self.params.unit_amount = Some(unit_amount);
+        self
+    }
+
+    pub fn line_amount(mut self, line_amount: BigDecimal) -> Self {
+        self.params.line_amount = Some(line_amount);
+        self
+    }
+
+    pub fn tax_amount(mut self, tax_amount: BigDecimal) -> Self {
+        self.params.tax_amount = Some(tax_amount);
+        self
+    }
+
+    pub fn account_code<S: Into<String>>(mut self, account_code: S) -> Self {
+        self.params.account_code = Some(account_code.into());
+        self
+    }
+
+    pub fn line_item_id(mut self, line_item_id: LineItemId) -> Self {
+        self.params.line_item_id = Some(line_item_id);
+        self
+    }
+
+    pub fn tax_type<S: Into<String>>(mut self, tax_type: S) -> Self {
+        self.params.tax_type = Some(tax_type.into());
+        self
+    }
+
+    /// Assigns the line item to `option` of the tracking category `name`.
+    pub fn tracking<S: Into<String>, T: Into<String>>(mut self, name: S, option: T) -> Self {
+        self.params.tracking.push(LineItemTracking{name: name.into(), option: option.into()});
+        self
+    }
+
+    pub fn discount_rate(mut self, discount_rate: BigDecimal) -> Self {
+        self.params.discount_rate = Some(discount_rate);
+        self
+    }
+
+    pub fn discount_amount(mut self, discount_amount: BigDecimal) -> Self {
+        self.params.discount_amount = Some(discount_amount);
+        self
+    }
+
+    pub fn build(self) -> LineItemParams {
+        self.params
     }
 }
 
-/// ... Some fields missing ...
-#[derive(Default, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "PascalCase")]
-pub struct InvoiceParams<'a> {
+pub struct InvoiceParams {
     #[serde(rename = "Type")]
     pub invoice_type: InvoiceType, // Required
-    pub contact: ContactIdParams<'a>, // Required
+    pub contact: ContactIdParams, // Required
     #[serde(skip_serializing_if = "Option::is_none")]
     pub date: Option<NaiveDate>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub due_date: Option<NaiveDate>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub invoice_number: Option<&'a str>,
+    pub invoice_number: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub reference: Option<&'a str>,
+    pub reference: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub url: Option<&'a str>,
+    pub url: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<InvoiceStatus>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sent_to_contact: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub line_amount_types: Option<LineAmountType>,
-    pub line_items: Vec<LineItemParams<'a>>, // Required
-    // ...
+    pub line_items: Vec<LineItemParams>, // Required
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub currency_rate: Option<BigDecimal>,
+    #[serde(rename = "BrandingThemeID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branding_theme_id: Option<BrandingThemeId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_payment_date: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub planned_payment_date: Option<NaiveDate>,
 }
 
-impl<'a> XmlSerializable for InvoiceParams<'a> {
+impl XmlSerializable for InvoiceParams {
     fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
         xml.element("Type", &self.invoice_type)?;
         xml.element("Contact", &self.contact)?;
@@ -148,7 +299,127 @@ impl<'a> XmlSerializable for InvoiceParams<'a> {
         xml.element_opt("Status", &self.status)?;
         xml.element_opt("SentToContact", &self.sent_to_contact)?;
         xml.element_opt("LineAmountTypes", &self.line_amount_types)?;
-        xml.array("LineItems", "LineItem", &self.line_items)
+        xml.array("LineItems", "LineItem", &self.line_items)?;
+        xml.element_opt("CurrencyCode", &self.currency_code)?;
+        xml.element_opt("CurrencyRate", &self.currency_rate)?;
+        xml.element_opt("BrandingThemeID", &self.branding_theme_id)?;
+        if let Some(date) = self.expected_payment_date {
+            xml.element("ExpectedPaymentDate", &date.format("%Y-%m-%d").to_string())?;
+        }
+        if let Some(date) = self.planned_payment_date {
+            xml.element("PlannedPaymentDate", &date.format("%Y-%m-%d").to_string())?;
+        }
+        Ok(())
+    }
+}
+
+impl InvoiceParams {
+    /// Starts building an invoice for `contact_id`; Xero requires at least one line item, so the
+    /// first is given here and any others are added with `line_item`.
+    pub fn builder(invoice_type: InvoiceType, contact_id: ContactId, line_item: LineItemParams) -> InvoiceParamsBuilder {
+        InvoiceParamsBuilder{params: InvoiceParams{
+            invoice_type: invoice_type,
+            contact: ContactIdParams::from(contact_id),
+            date: None,
+            due_date: None,
+            invoice_number: None,
+            reference: None,
+            url: None,
+            status: None,
+            sent_to_contact: None,
+            line_amount_types: None,
+            line_items: vec![line_item],
+            currency_code: None,
+            currency_rate: None,
+            branding_theme_id: None,
+            expected_payment_date: None,
+            planned_payment_date: None,
+        }}
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct InvoiceParamsBuilder {
+    params: InvoiceParams,
+}
+
+impl InvoiceParamsBuilder {
+    pub fn date(mut self, date: NaiveDate) -> Self {
+        self.params.date = Some(date);
+        self
+    }
+
+    pub fn due_date(mut self, due_date: NaiveDate) -> Self {
+        self.params.due_date = Some(due_date);
+        self
+    }
+
+    pub fn invoice_number<S: Into<String>>(mut self, invoice_number: S) -> Self {
+        self.params.invoice_number = Some(invoice_number.into());
+        self
+    }
+
+    pub fn reference<S: Into<String>>(mut self, reference: S) -> Self {
+        self.params.reference = Some(reference.into());
+        self
+    }
+
+    pub fn url<S: Into<String>>(mut self, url: S) -> Self {
+        self.params.url = Some(url.into());
+        self
+    }
+
+    pub fn status(mut self, status: InvoiceStatus) -> Self {
+        self.params.status = Some(status);
+        self
+    }
+
+    pub fn sent_to_contact(mut self, sent_to_contact: bool) -> Self {
+        self.params.sent_to_contact = Some(sent_to_contact);
+        self
+    }
+
+    pub fn line_amount_types(mut self, line_amount_types: LineAmountType) -> Self {
+        self.params.line_amount_types = Some(line_amount_types);
+        self
+    }
+
+    pub fn line_item(mut self, line_item: LineItemParams) -> Self {
+        self.params.line_items.push(line_item);
+        self
+    }
+
+    /// The currency the invoice is in, e.g. `"USD"`, if not the organisation's base currency.
+    pub fn currency_code<S: Into<String>>(mut self, currency_code: S) -> Self {
+        self.params.currency_code = Some(currency_code.into());
+        self
+    }
+
+    /// Units of the invoice's currency per unit of the base currency; Xero uses its daily rate if unset.
+    pub fn currency_rate(mut self, currency_rate: BigDecimal) -> Self {
+        self.params.currency_rate = Some(currency_rate);
+        self
+    }
+
+    pub fn branding_theme_id(mut self, branding_theme_id: BrandingThemeId) -> Self {
+        self.params.branding_theme_id = Some(branding_theme_id);
+        self
+    }
+
+    /// When the customer has said they will pay a sales invoice.
+    pub fn expected_payment_date(mut self, date: NaiveDate) -> Self {
+        self.params.expected_payment_date = Some(date);
+        self
+    }
+
+    /// When a bill is planned to be paid.
+    pub fn planned_payment_date(mut self, date: NaiveDate) -> Self {
+        self.params.planned_payment_date = Some(date);
+        self
+    }
+
+    pub fn build(self) -> InvoiceParams {
+        self.params
     }
 }
 
@@ -159,44 +430,363 @@ pub struct InvoiceSummary {
     #[serde(rename = "Type")]
     pub invoice_type: InvoiceType,
     #[serde(rename = "InvoiceID")]
-    pub invoice_id: String,
+    pub invoice_id: InvoiceId,
     pub invoice_number: String,
 }
 
-/// ... Some fields missing ...
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct LineItem {
+    #[serde(rename = "LineItemID")]
+    pub line_item_id: Option<LineItemId>,
+    #[serde(default)]
+    pub description: String,
+    pub item_code: Option<String>,
+    pub quantity: Option<f64>,
+    pub unit_amount: Option<BigDecimal>,
+    pub account_code: Option<String>,
+    pub tax_type: Option<String>,
+    pub tax_amount: Option<BigDecimal>,
+    pub line_amount: Option<BigDecimal>,
+    #[serde(default)]
+    pub tracking: Vec<LineItemTracking>,
+    pub discount_rate: Option<BigDecimal>,
+    pub discount_amount: Option<BigDecimal>,
+}
+
+/// A payment applied to an invoice.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct InvoicePayment {
+    #[serde(rename = "PaymentID")]
+    pub payment_id: PaymentId,
+    #[serde(default, deserialize_with = "encoding::deserialize_json_date_opt")]
+    pub date: Option<DateTime<Utc>>,
+    pub amount: BigDecimal,
+    pub reference: Option<String>,
+    pub currency_rate: Option<BigDecimal>,
+}
+
+/// A credit note allocated to an invoice.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct InvoiceCreditNote {
+    #[serde(rename = "CreditNoteID")]
+    pub credit_note_id: CreditNoteId,
+    pub credit_note_number: Option<String>,
+    #[serde(default, deserialize_with = "encoding::deserialize_json_date_opt")]
+    pub date: Option<DateTime<Utc>>,
+    pub applied_amount: Option<BigDecimal>,
+    pub total: Option<BigDecimal>,
+}
+
+/// A prepayment allocated to an invoice.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct InvoicePrepayment {
+    #[serde(rename = "PrepaymentID")]
+    pub prepayment_id: PrepaymentId,
+    #[serde(default, deserialize_with = "encoding::deserialize_json_date_opt")]
+    pub date: Option<DateTime<Utc>>,
+    pub applied_amount: Option<BigDecimal>,
+    pub total: Option<BigDecimal>,
+}
+
+/// An overpayment allocated to an invoice.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct InvoiceOverpayment {
+    #[serde(rename = "OverpaymentID")]
+    pub overpayment_id: OverpaymentId,
+    #[serde(default, deserialize_with = "encoding::deserialize_json_date_opt")]
+    pub date: Option<DateTime<Utc>>,
+    pub applied_amount: Option<BigDecimal>,
+    pub total: Option<BigDecimal>,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Invoice {
     pub contact: ContactSummary,
     #[serde(rename = "DateString")]
     pub date: NaiveDateTime,
-    #[serde(rename = "DateDueString")]
+    #[serde(rename = "DueDateString")]
     pub due_date: Option<NaiveDateTime>,
     pub status: InvoiceStatus,
     pub line_amount_types: LineAmountType,
-    pub subtotal: Option<f64>,
-    pub total_tax: f64,
-    pub total: f64,
-    pub total_discount: Option<f64>,
+    #[serde(default)]
+    pub line_items: Vec<LineItem>,
+    #[serde(rename = "SubTotal")]
+    pub subtotal: Option<BigDecimal>,
+    pub total_tax: BigDecimal,
+    pub total: BigDecimal,
+    pub total_discount: Option<BigDecimal>,
     #[serde(rename = "Type")]
     pub invoice_type: InvoiceType,
     #[serde(rename = "InvoiceID")]
-    pub invoice_id: String,
+    pub invoice_id: InvoiceId,
     pub invoice_number: String,
     pub reference: Option<String>,
-    // ...
+    #[serde(default)]
+    pub payments: Vec<InvoicePayment>,
+    #[serde(default)]
+    pub credit_notes: Vec<InvoiceCreditNote>,
+    #[serde(default)]
+    pub prepayments: Vec<InvoicePrepayment>,
+    #[serde(default)]
+    pub overpayments: Vec<InvoiceOverpayment>,
+    #[serde(default)]
+    pub amount_due: BigDecimal,
+    #[serde(default)]
+    pub amount_paid: BigDecimal,
+    #[serde(default)]
+    pub amount_credited: BigDecimal,
+    pub currency_code: Option<String>,
+    pub currency_rate: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "encoding::deserialize_json_date_opt")]
+    pub fully_paid_on_date: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "encoding::deserialize_json_date_opt")]
+    pub expected_payment_date: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "encoding::deserialize_json_date_opt")]
+    pub planned_payment_date: Option<DateTime<Utc>>,
+    #[serde(rename = "BrandingThemeID")]
+    pub branding_theme_id: Option<BrandingThemeId>,
+    #[serde(default)]
+    pub has_attachments: bool,
+    #[serde(rename = "UpdatedDateUTC")]
+    #[serde(default, deserialize_with = "encoding::deserialize_json_date_opt")]
+    pub updated_date_utc: Option<DateTime<Utc>>,
 }
 
 impl Invoice {
-    pub fn put(client: &Client, invoice: InvoiceParams) -> Result<Invoice, Error> {
+    pub fn get(client: &Client, id: InvoiceId) -> Result<Invoice, Error> {
+        let invoices: Invoices = client.get(&format!("/Invoices/{}", id))?;
+        invoices.invoices.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    /// Gets the invoice as it is in Xero now, bypassing any cache, to check a status change.
+    fn get_current(client: &Client, id: InvoiceId) -> Result<Invoice, Error> {
+        let invoices: Invoices = client.get_uncached(&format!("/Invoices/{}", id))?;
+        invoices.invoices.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    pub fn put(client: &Client, invoice: InvoiceParams, idempotency_key: Option<&str>) -> Result<Invoice, Error> {
         let mut body = Vec::new();
         {
             let mut xml = XmlWriter::new(&mut body);
             xml.element("Invoice", &invoice)?;
         }
-        let invoices: Invoices = client.put("/Invoices", body.as_slice())?;
-        Ok(invoices.invoices.into_iter().next().expect("Expected invoice after successful PUT"))
+        let invoices: Invoices = client.put("/Invoices", body.as_slice(), idempotency_key)?;
+        invoices.invoices.into_iter().next().ok_or(Error::EmptyResponse)
     }
+
+    /// Updates an invoice. If `invoice.status` would change its status, the invoice is fetched
+    /// first to check the change is allowed, as with `submit`, `authorise`, `void` and `delete`.
+    pub fn update(client: &Client, id: InvoiceId, invoice: InvoiceParams, idempotency_key: Option<&str>) -> Result<Invoice, Error> {
+        if let Some(to) = invoice.status {
+            let current = Invoice::get_current(client, id)?;
+            if current.status != to {
+                current.check_transition(to).map_err(|err| Error::Invoice(err, None))?;
+            }
+        }
+        let mut body = Vec::new();
+        {
+            let mut xml = XmlWriter::new(&mut body);
+            xml.element("Invoice", &invoice)?;
+        }
+        let invoices: Invoices = client.post(&format!("/Invoices/{}", id), body.as_slice(), idempotency_key)?;
+        invoices.invoices.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    /// Converts an amount on this invoice to the organisation's base currency, using the rate Xero applied.
+    ///
+    /// Returns `None` if the invoice has no currency rate, or a rate of zero.
+    pub fn to_base_currency(&self, amount: &BigDecimal) -> Option<BigDecimal> {
+        self.currency_rate.as_ref().and_then(|rate| currencies::to_base_currency(amount, rate))
+    }
+
+    /// The invoice's total in the organisation's base currency, if it has a usable currency rate.
+    pub fn base_currency_total(&self) -> Option<BigDecimal> {
+        self.to_base_currency(&self.total)
+    }
+
+    /// Submits a draft invoice for approval.
+    pub fn submit(client: &Client, id: InvoiceId, idempotency_key: Option<&str>) -> Result<Invoice, Error> {
+        Invoice::transition(client, id, InvoiceStatus::Submitted, idempotency_key)
+    }
+
+    /// Approves a draft or submitted invoice, after which it can be paid.
+    pub fn authorise(client: &Client, id: InvoiceId, idempotency_key: Option<&str>) -> Result<Invoice, Error> {
+        Invoice::transition(client, id, InvoiceStatus::Authorised, idempotency_key)
+    }
+
+    /// Voids an authorised invoice; any payments and credit allocations must be removed first.
+    pub fn void(client: &Client, id: InvoiceId, idempotency_key: Option<&str>) -> Result<Invoice, Error> {
+        Invoice::transition(client, id, InvoiceStatus::Voided, idempotency_key)
+    }
+
+    /// Deletes a draft or submitted invoice.
+    pub fn delete(client: &Client, id: InvoiceId, idempotency_key: Option<&str>) -> Result<Invoice, Error> {
+        Invoice::transition(client, id, InvoiceStatus::Deleted, idempotency_key)
+    }
+
+    /// Checks Xero would allow this invoice's status to change to `to`.
+    ///
+    /// An invoice can't be voided while payments, credit notes, prepayments or overpayments are
+    /// allocated to it.
+    pub fn check_transition(&self, to: InvoiceStatus) -> Result<(), InvoiceError> {
+        if !self.status.can_transition_to(to) {
+            return Err(InvoiceError::InvalidTransition{from: self.status, to: to});
+        }
+        if to == InvoiceStatus::Voided && self.has_allocations() {
+            return Err(InvoiceError::HasPayments);
+        }
+        Ok(())
+    }
+
+    fn has_allocations(&self) -> bool {
+        let zero = BigDecimal::from(0);
+        let applied = |amount: &Option<BigDecimal>| amount.as_ref().map_or(false, |amount| *amount != zero);
+        self.amount_paid != zero
+            || self.amount_credited != zero
+            || self.payments.iter().any(|payment| payment.amount != zero)
+            || self.credit_notes.iter().any(|credit_note| applied(&credit_note.applied_amount))
+            || self.prepayments.iter().any(|prepayment| applied(&prepayment.applied_amount))
+            || self.overpayments.iter().any(|overpayment| applied(&overpayment.applied_amount))
+    }
+
+    /// Updates the invoice's status, after checking its current status allows it.
+    fn transition(client: &Client, id: InvoiceId, to: InvoiceStatus, idempotency_key: Option<&str>) -> Result<Invoice, Error> {
+        let invoice = Invoice::get_current(client, id)?;
+        invoice.check_transition(to).map_err(|err| Error::Invoice(err, None))?;
+
+        let mut body = Vec::new();
+        {
+            let mut xml = XmlWriter::new(&mut body);
+            xml.start_element("Invoice")?;
+            xml.element("Status", &to)?;
+            xml.end_element()?;
+        }
+        let invoices: Invoices = client.post(&format!("/Invoices/{}", id), body.as_slice(), idempotency_key)?;
+        invoices.invoices.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    /// Emails the invoice to its contact and anyone included in the contact's emails, as
+    /// Xero would from the invoice's page.
+    pub fn email(client: &Client, id: InvoiceId, idempotency_key: Option<&str>) -> Result<(), Error> {
+        client.post_empty(&format!("/Invoices/{}/Email", id), &[], idempotency_key).map_err(invoice_error)
+    }
+
+    /// The URL where the contact can view and pay the invoice online.
+    pub fn online_url(client: &Client, id: InvoiceId) -> Result<String, Error> {
+        let result: Result<OnlineInvoices, Error> = client.get(&format!("/Invoices/{}/OnlineInvoice", id));
+        let online_invoices = result.map_err(invoice_error)?;
+        online_invoices.online_invoices.into_iter().next()
+            .map(|online_invoice| online_invoice.online_invoice_url)
+            .ok_or(Error::EmptyResponse)
+    }
+
+    /// The invoice as a PDF, as it would be sent to the contact.
+    pub fn pdf(client: &Client, id: InvoiceId) -> Result<Vec<u8>, Error> {
+        client.get_binary(&format!("/Invoices/{}", id), "application/pdf")
+    }
+
+    /// Like `pdf`, but reads the PDF as it's downloaded, e.g. to stream it to a file.
+    pub fn pdf_reader(client: &Client, id: InvoiceId) -> Result<Box<Read + Send>, Error> {
+        client.get_reader(&format!("/Invoices/{}", id), "application/pdf")
+    }
+}
+
+/// A reason an action on an invoice was refused, by Xero or before it was sent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InvoiceError {
+    /// The invoice can't move from its current status to the requested one, e.g. a paid
+    /// invoice can't be voided.
+    InvalidTransition {
+        from: InvoiceStatus,
+        to: InvoiceStatus,
+    },
+    /// The invoice has payments or credit allocated, which must be removed before it can be
+    /// voided.
+    HasPayments,
+    /// The contact has no email address to send the invoice to.
+    NoEmailAddress,
+    /// The invoice's status doesn't allow it, e.g. drafts can't be emailed.
+    InvalidStatus,
+    /// Only sales (`ACCREC`) invoices can be emailed or viewed online.
+    NotSalesInvoice,
+    /// The organisation has sent as many emails as Xero allows in a day.
+    EmailLimitReached,
+}
+
+impl fmt::Display for InvoiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InvoiceError::InvalidTransition{from, to} => {
+                write!(f, "an invoice can't change from {} to {}", from.as_str(), to.as_str())
+            }
+            _ => f.write_str(error::Error::description(self)),
+        }
+    }
+}
+
+impl error::Error for InvoiceError {
+    fn description(&self) -> &str {
+        match *self {
+            InvoiceError::InvalidTransition{..} => "invalid invoice status transition",
+            InvoiceError::HasPayments => "the invoice has payments or credit allocated",
+            InvoiceError::NoEmailAddress => "the contact has no email address",
+            InvoiceError::InvalidStatus => "the invoice's status doesn't allow this",
+            InvoiceError::NotSalesInvoice => "only sales invoices can be emailed or viewed online",
+            InvoiceError::EmailLimitReached => "the daily email limit has been reached",
+        }
+    }
+}
+
+/// The messages Xero reports when it refuses to email an invoice or give its online URL.
+const INVOICE_ERROR_MESSAGES: &[(&str, InvoiceError)] = &[
+    ("Daily Email Rate Limit Exceeded", InvoiceError::EmailLimitReached),
+    ("The contact does not have an email address", InvoiceError::NoEmailAddress),
+    ("Invoice not of valid status for sending by email", InvoiceError::InvalidStatus),
+    ("Invalid invoice status. Online invoices are not available for this invoice", InvoiceError::InvalidStatus),
+    ("Invalid invoice type. Only sales invoices can be emailed", InvoiceError::NotSalesInvoice),
+    ("Invalid invoice type. Online invoices are only available for Accounts Receivable invoices", InvoiceError::NotSalesInvoice),
+];
+
+/// Maps the validation errors Xero reports for invoice actions to an `InvoiceError`.
+///
+/// Only the exact messages in `INVOICE_ERROR_MESSAGES` are mapped, ignoring case and a trailing
+/// full stop; any other error is returned unchanged as `Error::Xero`.
+fn invoice_error(err: Error) -> Error {
+    let kind = match err {
+        Error::Xero(RequestError::Validation(ref validation), _) => {
+            let elements = validation.elements.iter().flat_map(|element| element.validation_errors.iter());
+            let mut messages = Some(&validation.message).into_iter().chain(elements.map(|e| &e.message));
+            messages.find_map(|message| {
+                let message = message.trim().trim_end_matches('.');
+                INVOICE_ERROR_MESSAGES.iter()
+                    .find(|&&(known, _)| known.eq_ignore_ascii_case(message))
+                    .map(|&(_, kind)| kind)
+            })
+        }
+        _ => None,
+    };
+    match (kind, err) {
+        (Some(kind), Error::Xero(_, meta)) => Error::Invoice(kind, Some(meta)),
+        (_, err) => err,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct OnlineInvoice {
+    online_invoice_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct OnlineInvoices {
+    online_invoices: Vec<OnlineInvoice>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -206,12 +796,44 @@ pub struct Invoices {
 }
 
 impl Invoices {
-    pub fn put(client: &Client, invoices: Vec<InvoiceParams>) -> Result<Invoices, Error> {
+    pub fn get(client: &Client) -> Result<Invoices, Error> {
+        client.get("/Invoices")
+    }
+
+    /// The contact's invoices with any of `statuses`, or all of its invoices if `statuses` is empty.
+    pub fn get_for_contact(client: &Client, contact_id: ContactId, statuses: &[InvoiceStatus]) -> Result<Invoices, Error> {
+        client.get(&Invoices::contact_path(contact_id, statuses))
+    }
+
+    pub(crate) fn contact_path(contact_id: ContactId, statuses: &[InvoiceStatus]) -> String {
+        let mut path = format!("/Invoices?ContactIDs={}", contact_id);
+        if !statuses.is_empty() {
+            let statuses: Vec<&str> = statuses.iter().map(InvoiceStatus::as_str).collect();
+            path.push_str("&Statuses=");
+            path.push_str(&statuses.join(","));
+        }
+        path
+    }
+
+    pub fn put(client: &Client, invoices: Vec<InvoiceParams>, idempotency_key: Option<&str>) -> Result<Invoices, Error> {
         let mut body = Vec::new();
         {
             let mut xml = XmlWriter::new(&mut body);
             xml.array("Invoices", "Invoice", &invoices)?;
         }
-        client.put("/Invoices", body.as_slice())
+        client.put("/Invoices", body.as_slice(), idempotency_key)
+    }
+
+    /// Creates each invoice independently, so that one invalid element doesn't fail the others.
+    pub fn put_each(client: &Client, invoices: Vec<InvoiceParams>, idempotency_key: Option<&str>) -> Result<BulkResults<Invoice>, Error> {
+        bulk::put_each(client, &invoices, idempotency_key)
     }
 }
+
+impl BulkParams for InvoiceParams {
+    type Resource = Invoice;
+
+    fn endpoint() -> &'static str { "/Invoices" }
+    fn collection() -> &'static str { "Invoices" }
+    fn element() -> &'static str { "Invoice" }
+}
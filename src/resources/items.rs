@@ -2,8 +2,10 @@ use bigdecimal::BigDecimal;
 use client::Client;
 use encoding::{XmlError, XmlSerializable, XmlWriter};
 use error::Error;
+use resources::bulk::{self, BulkParams, BulkResults};
+use resources::ids::ItemId;
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ItemDetails {
     pub unit_price: Option<BigDecimal>,
@@ -25,18 +27,18 @@ impl<'a> XmlSerializable for ItemDetails {
     }
 }
 
-#[derive(Default, Serialize)]
+#[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "PascalCase")]
-pub struct ItemParams<'a> {
-    pub code: &'a str,
+pub struct ItemParams {
+    pub code: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub description: Option<&'a str>,
+    pub description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub purchase_description: Option<&'a str>,
+    pub purchase_description: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<&'a str>,
+    pub name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub inventory_asset_account_code: Option<&'a str>,
+    pub inventory_asset_account_code: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_sold: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -47,7 +49,7 @@ pub struct ItemParams<'a> {
     pub sales_details: Option<ItemDetails>,
 }
 
-impl<'a> XmlSerializable for ItemParams<'a> {
+impl XmlSerializable for ItemParams {
     fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
         xml.element("Code", &self.code)?;
         xml.element_opt("Description", &self.description)?;
@@ -61,12 +63,79 @@ impl<'a> XmlSerializable for ItemParams<'a> {
     }
 }
 
+impl ItemParams {
+    pub fn builder<S: Into<String>>(code: S) -> ItemParamsBuilder {
+        ItemParamsBuilder{params: ItemParams{
+            code: code.into(),
+            description: None,
+            purchase_description: None,
+            name: None,
+            inventory_asset_account_code: None,
+            is_sold: None,
+            is_purchased: None,
+            purchase_details: None,
+            sales_details: None,
+        }}
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ItemParamsBuilder {
+    params: ItemParams,
+}
+
+impl ItemParamsBuilder {
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.params.description = Some(description.into());
+        self
+    }
+
+    pub fn purchase_description<S: Into<String>>(mut self, purchase_description: S) -> Self {
+        self.params.purchase_description = Some(purchase_description.into());
+        self
+    }
+
+    pub fn name<S: Into<String>>(mut self, name: S) -> Self {
+        self.params.name = Some(name.into());
+        self
+    }
+
+    pub fn inventory_asset_account_code<S: Into<String>>(mut self, account_code: S) -> Self {
+        self.params.inventory_asset_account_code = Some(account_code.into());
+        self
+    }
+
+    pub fn is_sold(mut self, is_sold: bool) -> Self {
+        self.params.is_sold = Some(is_sold);
+        self
+    }
+
+    pub fn is_purchased(mut self, is_purchased: bool) -> Self {
+        self.params.is_purchased = Some(is_purchased);
+        self
+    }
+
+    pub fn purchase_details(mut self, purchase_details: ItemDetails) -> Self {
+        self.params.purchase_details = Some(purchase_details);
+        self
+    }
+
+    pub fn sales_details(mut self, sales_details: ItemDetails) -> Self {
+        self.params.sales_details = Some(sales_details);
+        self
+    }
+
+    pub fn build(self) -> ItemParams {
+        self.params
+    }
+}
+
 /// ... Some fields missing ...
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Item {
     #[serde(rename = "ItemID")]
-    pub item_id: String,
+    pub item_id: ItemId,
     pub code: String,
     pub description: Option<String>,
     pub purchase_description: Option<String>,
@@ -80,14 +149,33 @@ pub struct Item {
 }
 
 impl Item {
-    pub fn put(client: &Client, item: ItemParams) -> Result<Item, Error> {
+    pub fn get(client: &Client, id: ItemId) -> Result<Item, Error> {
+        let items: Items = client.get(&format!("/Items/{}", id))?;
+        items.items.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    pub fn put(client: &Client, item: ItemParams, idempotency_key: Option<&str>) -> Result<Item, Error> {
         let mut body = Vec::new();
         {
             let mut xml = XmlWriter::new(&mut body);
             xml.element("Item", &item)?;
         }
-        let items: Items = client.put("/Items", body.as_slice())?;
-        Ok(items.items.into_iter().next().expect("Expected item after successful PUT"))
+        let items: Items = client.put("/Items", body.as_slice(), idempotency_key)?;
+        items.items.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    pub fn update(client: &Client, id: ItemId, item: ItemParams, idempotency_key: Option<&str>) -> Result<Item, Error> {
+        let mut body = Vec::new();
+        {
+            let mut xml = XmlWriter::new(&mut body);
+            xml.element("Item", &item)?;
+        }
+        let items: Items = client.post(&format!("/Items/{}", id), body.as_slice(), idempotency_key)?;
+        items.items.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    pub fn delete(client: &Client, id: ItemId, idempotency_key: Option<&str>) -> Result<(), Error> {
+        client.delete(&format!("/Items/{}", id), idempotency_key)
     }
 }
 
@@ -98,12 +186,29 @@ pub struct Items {
 }
 
 impl Items {
-    pub fn put(client: &Client, items: Vec<ItemParams>) -> Result<Items, Error> {
+    pub fn get(client: &Client) -> Result<Items, Error> {
+        client.get("/Items")
+    }
+
+    pub fn put(client: &Client, items: Vec<ItemParams>, idempotency_key: Option<&str>) -> Result<Items, Error> {
         let mut body = Vec::new();
         {
             let mut xml = XmlWriter::new(&mut body);
             xml.array("Items", "Item", &items)?;
         }
-        client.put("/Items", body.as_slice())
+        client.put("/Items", body.as_slice(), idempotency_key)
     }
+
+    /// Creates each item independently, so that one invalid element doesn't fail the others.
+    pub fn put_each(client: &Client, items: Vec<ItemParams>, idempotency_key: Option<&str>) -> Result<BulkResults<Item>, Error> {
+        bulk::put_each(client, &items, idempotency_key)
+    }
+}
+
+impl BulkParams for ItemParams {
+    type Resource = Item;
+
+    fn endpoint() -> &'static str { "/Items" }
+    fn collection() -> &'static str { "Items" }
+    fn element() -> &'static str { "Item" }
 }
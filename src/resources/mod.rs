@@ -1,4 +1,11 @@
+pub mod bulk;
+pub mod contact_groups;
+pub mod contact_merge;
 pub mod contacts;
+pub mod currencies;
+pub mod history;
+pub mod ids;
+pub mod invoice_totals;
 pub mod invoices;
 pub mod items;
 pub mod payments;
@@ -1,10 +1,12 @@
 
 use bigdecimal::BigDecimal;
-use chrono::{NaiveDate, Utc};
+use chrono::NaiveDate;
 use client::Client;
 use encoding::{XmlError, XmlSerializable, XmlWriter};
 use error::Error;
 
+use resources::bulk::{self, BulkParams, BulkResults};
+use resources::ids::{AccountId, CreditNoteId, InvoiceId, OverpaymentId, PaymentId, PrepaymentId};
 use resources::invoices::InvoiceSummary;
 
 #[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
@@ -61,101 +63,134 @@ impl XmlSerializable for PaymentType {
     }
 }
 
-#[derive(Serialize)]
-pub enum PaymentInvoice<'a> {
+#[derive(Clone, Debug, Serialize)]
+pub enum PaymentInvoice {
     #[serde(rename = "InvoiceID")]
-    InvoiceId(&'a str),
-    InvoiceNumber(&'a str),
+    InvoiceId(InvoiceId),
+    InvoiceNumber(String),
 }
 
-impl<'a> XmlSerializable for PaymentInvoice<'a> {
+impl XmlSerializable for PaymentInvoice {
     fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
         match *self {
-            PaymentInvoice::InvoiceId(id) => xml.element("InvoiceID", &id),
-            PaymentInvoice::InvoiceNumber(number) => xml.element("InvoiceNumber", &number),
+            PaymentInvoice::InvoiceId(ref id) => xml.element("InvoiceID", id),
+            PaymentInvoice::InvoiceNumber(ref number) => xml.element("InvoiceNumber", number),
         }
     }
 }
 
-#[derive(Serialize)]
-pub enum PaymentCreditNote<'a> {
+#[derive(Clone, Debug, Serialize)]
+pub enum PaymentCreditNote {
     #[serde(rename = "CreditNoteID")]
-    CreditNoteId(&'a str),
-    CreditNoteNumber(&'a str),
+    CreditNoteId(CreditNoteId),
+    CreditNoteNumber(String),
 }
 
-impl<'a> XmlSerializable for PaymentCreditNote<'a> {
+impl XmlSerializable for PaymentCreditNote {
     fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
         match *self {
-            PaymentCreditNote::CreditNoteId(id) => xml.element("CreditNoteID", &id),
-            PaymentCreditNote::CreditNoteNumber(number) => xml.element("CreditNumber", &number),
+            PaymentCreditNote::CreditNoteId(ref id) => xml.element("CreditNoteID", id),
+            PaymentCreditNote::CreditNoteNumber(ref number) => xml.element("CreditNumber", number),
         }
     }
 }
 
-#[derive(Serialize)]
-pub enum PaymentPrepayment<'a> {
-    #[serde(rename = "CreditNoteID")]
-    PrepaymentId(&'a str),
+#[derive(Clone, Debug, Serialize)]
+pub enum PaymentPrepayment {
+    #[serde(rename = "PrepaymentID")]
+    PrepaymentId(PrepaymentId),
 }
 
-impl<'a> XmlSerializable for PaymentPrepayment<'a> {
+impl XmlSerializable for PaymentPrepayment {
     fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
         match *self {
-            PaymentPrepayment::PrepaymentId(id) => xml.element("PrepaymentID", &id),
+            PaymentPrepayment::PrepaymentId(ref id) => xml.element("PrepaymentID", id),
         }
     }
 }
 
-#[derive(Serialize)]
-pub enum PaymentOverpayment<'a> {
+#[derive(Clone, Debug, Serialize)]
+pub enum PaymentOverpayment {
     #[serde(rename = "OverpaymentID")]
-    OverpaymentId(&'a str),
+    OverpaymentId(OverpaymentId),
 }
 
-impl<'a> XmlSerializable for PaymentOverpayment<'a> {
+impl XmlSerializable for PaymentOverpayment {
     fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
         match *self {
-            PaymentOverpayment::OverpaymentId(id) => xml.element("OverpaymentID", &id),
+            PaymentOverpayment::OverpaymentId(ref id) => xml.element("OverpaymentID", id),
         }
     }
 }
 
-#[derive(Serialize)]
-pub enum PaymentAccount<'a> {
+#[derive(Clone, Debug, Serialize)]
+pub enum PaymentAccount {
     #[serde(rename = "AccountID")]
-    AccountId(&'a str),
-    Code(&'a str),
+    AccountId(AccountId),
+    Code(String),
 }
 
-impl<'a> XmlSerializable for PaymentAccount<'a> {
+impl XmlSerializable for PaymentAccount {
     fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
         match *self {
-            PaymentAccount::AccountId(id) => xml.element("AccountID", &id),
-            PaymentAccount::Code(id) => xml.element("Code", &id),
+            PaymentAccount::AccountId(ref id) => xml.element("AccountID", id),
+            PaymentAccount::Code(ref code) => xml.element("Code", code),
         }
     }
 }
 
+/// The document a payment is applied to, which Xero requires for every payment.
+#[derive(Clone, Debug)]
+pub enum PaymentDocument {
+    Invoice(PaymentInvoice),
+    CreditNote(PaymentCreditNote),
+    Prepayment(PaymentPrepayment),
+    Overpayment(PaymentOverpayment),
+}
+
+impl From<PaymentInvoice> for PaymentDocument {
+    fn from(invoice: PaymentInvoice) -> PaymentDocument {
+        PaymentDocument::Invoice(invoice)
+    }
+}
+
+impl From<PaymentCreditNote> for PaymentDocument {
+    fn from(credit_note: PaymentCreditNote) -> PaymentDocument {
+        PaymentDocument::CreditNote(credit_note)
+    }
+}
+
+impl From<PaymentPrepayment> for PaymentDocument {
+    fn from(prepayment: PaymentPrepayment) -> PaymentDocument {
+        PaymentDocument::Prepayment(prepayment)
+    }
+}
+
+impl From<PaymentOverpayment> for PaymentDocument {
+    fn from(overpayment: PaymentOverpayment) -> PaymentDocument {
+        PaymentDocument::Overpayment(overpayment)
+    }
+}
+
 /// ... Some fields missing ...
-#[derive(Serialize)]
+#[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "PascalCase")]
-pub struct PaymentParams<'a> {
+pub struct PaymentParams {
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub invoice: Option<PaymentInvoice<'a>>,
+    pub invoice: Option<PaymentInvoice>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub credit_note: Option<PaymentCreditNote<'a>>,
+    pub credit_note: Option<PaymentCreditNote>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub prepayment: Option<PaymentPrepayment<'a>>,
+    pub prepayment: Option<PaymentPrepayment>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub overpayment: Option<PaymentOverpayment<'a>>,
+    pub overpayment: Option<PaymentOverpayment>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub account: Option<PaymentAccount<'a>>,
+    pub account: Option<PaymentAccount>,
 
     pub date: NaiveDate,
     pub amount: BigDecimal,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub reference: Option<&'a str>, // ie. a memo
+    pub reference: Option<String>, // ie. a memo
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_reconciled: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -165,25 +200,7 @@ pub struct PaymentParams<'a> {
     // ...
 }
 
-impl<'a> Default for PaymentParams<'a> {
-    fn default() -> Self {
-        PaymentParams{
-            invoice: None,
-            credit_note: None,
-            prepayment: None,
-            overpayment: None,
-            account: None,
-            date: Utc::today().naive_utc(),
-            amount: BigDecimal::from(0).with_scale(4),
-            reference: None,
-            is_reconciled: None,
-            status: None,
-            payment_type: None,
-        }
-    }
-}
-
-impl<'a> XmlSerializable for PaymentParams<'a> {
+impl XmlSerializable for PaymentParams {
     fn write(&self, xml: &mut XmlWriter) ->  Result<(), XmlError> {
         xml.element_opt("Invoice", &self.invoice)?;
         xml.element_opt("CreditNote", &self.credit_note)?;
@@ -199,17 +216,76 @@ impl<'a> XmlSerializable for PaymentParams<'a> {
     }
 }
 
+impl PaymentParams {
+    /// Starts building a payment of `amount` on `date` against `document`, from (or to) `account`.
+    pub fn builder<D: Into<PaymentDocument>>(document: D, account: PaymentAccount, date: NaiveDate, amount: BigDecimal) -> PaymentParamsBuilder {
+        let mut params = PaymentParams{
+            invoice: None,
+            credit_note: None,
+            prepayment: None,
+            overpayment: None,
+            account: Some(account),
+            date: date,
+            amount: amount,
+            reference: None,
+            is_reconciled: None,
+            status: None,
+            payment_type: None,
+        };
+        match document.into() {
+            PaymentDocument::Invoice(invoice) => params.invoice = Some(invoice),
+            PaymentDocument::CreditNote(credit_note) => params.credit_note = Some(credit_note),
+            PaymentDocument::Prepayment(prepayment) => params.prepayment = Some(prepayment),
+            PaymentDocument::Overpayment(overpayment) => params.overpayment = Some(overpayment),
+        }
+        PaymentParamsBuilder{params: params}
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PaymentParamsBuilder {
+    params: PaymentParams,
+}
+
+impl PaymentParamsBuilder {
+    pub fn reference<S: Into<String>>(mut self, reference: S) -> Self {
+        self.params.reference = Some(reference.into());
+        self
+    }
+
+    pub fn is_reconciled(mut self, is_reconciled: bool) -> Self {
+        self.params.is_reconciled = Some(is_reconciled);
+        self
+    }
+
+    pub fn status(mut self, status: PaymentStatus) -> Self {
+        self.params.status = Some(status);
+        self
+    }
+
+    pub fn payment_type(mut self, payment_type: PaymentType) -> Self {
+        self.params.payment_type = Some(payment_type);
+        self
+    }
+
+    pub fn build(self) -> PaymentParams {
+        self.params
+    }
+}
+
 /// ... Some fields missing ...
 #[derive(Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct Payment {
     #[serde(rename = "PaymentID")]
-    pub payment_id: String,
+    pub payment_id: PaymentId,
     // FIXME: Invoice has DateString, but I guess Payment might not---
     // TODO: see if this is actually Optional or whether we should just use C# Date format
     // #[serde(rename = "DateString")]
     // pub date: NaiveDateTime,
     pub amount: BigDecimal,
+    /// The rate applied to a payment in a foreign currency, in units per unit of the base currency.
+    pub currency_rate: Option<BigDecimal>,
     pub payment_type: PaymentType,
     pub status: PaymentStatus,
     pub is_reconciled: bool,
@@ -219,14 +295,32 @@ pub struct Payment {
 }
 
 impl Payment {
-    pub fn put(client: &Client, payment: PaymentParams) -> Result<Payment, Error> {
+    pub fn get(client: &Client, id: PaymentId) -> Result<Payment, Error> {
+        let payments: Payments = client.get(&format!("/Payments/{}", id))?;
+        payments.payments.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    pub fn put(client: &Client, payment: PaymentParams, idempotency_key: Option<&str>) -> Result<Payment, Error> {
         let mut body = Vec::new();
         {
             let mut xml = XmlWriter::new(&mut body);
             xml.element("Payment", &payment)?;
         }
-        let payments: Payments = client.put("/Payments", body.as_slice())?;
-        Ok(payments.payments.into_iter().next().expect("Expected payment after successful PUT"))
+        let payments: Payments = client.put("/Payments", body.as_slice(), idempotency_key)?;
+        payments.payments.into_iter().next().ok_or(Error::EmptyResponse)
+    }
+
+    /// Payments can't be modified, only deleted by updating their status.
+    pub fn delete(client: &Client, id: PaymentId, idempotency_key: Option<&str>) -> Result<Payment, Error> {
+        let mut body = Vec::new();
+        {
+            let mut xml = XmlWriter::new(&mut body);
+            xml.start_element("Payment")?;
+            xml.element("Status", &PaymentStatus::Deleted)?;
+            xml.end_element()?;
+        }
+        let payments: Payments = client.post(&format!("/Payments/{}", id), body.as_slice(), idempotency_key)?;
+        payments.payments.into_iter().next().ok_or(Error::EmptyResponse)
     }
 }
 
@@ -237,12 +331,25 @@ pub struct Payments {
 }
 
 impl Payments {
-    pub fn put(client: &Client, payments: Vec<PaymentParams>) -> Result<Payments, Error> {
+    pub fn put(client: &Client, payments: Vec<PaymentParams>, idempotency_key: Option<&str>) -> Result<Payments, Error> {
         let mut body = Vec::new();
         {
             let mut xml = XmlWriter::new(&mut body);
             xml.array("Payments", "Payment", &payments)?;
         }
-        client.put("/Payments", body.as_slice())
+        client.put("/Payments", body.as_slice(), idempotency_key)
+    }
+
+    /// Creates each payment independently, so that one invalid element doesn't fail the others.
+    pub fn put_each(client: &Client, payments: Vec<PaymentParams>, idempotency_key: Option<&str>) -> Result<BulkResults<Payment>, Error> {
+        bulk::put_each(client, &payments, idempotency_key)
     }
 }
+
+impl BulkParams for PaymentParams {
+    type Resource = Payment;
+
+    fn endpoint() -> &'static str { "/Payments" }
+    fn collection() -> &'static str { "Payments" }
+    fn element() -> &'static str { "Payment" }
+}
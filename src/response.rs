@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+
+/// The rate limits Xero reported in a response's headers.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RateLimitStatus {
+    /// Calls remaining today for this organisation (`X-DayLimit-Remaining`).
+    pub day_remaining: Option<u32>,
+    /// Calls remaining this minute for this organisation (`X-MinLimit-Remaining`).
+    pub minute_remaining: Option<u32>,
+    /// Calls remaining this minute across the whole application (`X-AppMinLimit-Remaining`).
+    pub app_minute_remaining: Option<u32>,
+    /// Seconds to wait before retrying a rate limited request (`Retry-After`).
+    pub retry_after: Option<u32>,
+    /// Which limit was exceeded, e.g. `minute` (`X-Rate-Limit-Problem`).
+    pub problem: Option<String>,
+}
+
+/// Details of an HTTP response from Xero, such as the ID to quote when contacting Xero support.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResponseMeta {
+    pub status: u16,
+    /// The `Xero-Correlation-Id` which identifies the request to Xero.
+    pub correlation_id: Option<String>,
+    pub date: Option<DateTime<Utc>>,
+    pub rate_limit: RateLimitStatus,
+    /// Every header in the response, in the order they were received.
+    pub headers: Vec<(String, String)>,
+}
+
+impl ResponseMeta {
+    pub fn new(status: u16, headers: Vec<(String, String)>) -> ResponseMeta {
+        let mut meta = ResponseMeta{status: status, headers: headers, ..ResponseMeta::default()};
+        meta.correlation_id = meta.header("Xero-Correlation-Id").map(|s| s.to_string());
+        meta.date = meta.header("Date")
+            .and_then(|s| DateTime::parse_from_rfc2822(s).ok())
+            .map(|date| date.with_timezone(&Utc));
+        meta.rate_limit = RateLimitStatus{
+            day_remaining: meta.header("X-DayLimit-Remaining").and_then(|s| s.parse().ok()),
+            minute_remaining: meta.header("X-MinLimit-Remaining").and_then(|s| s.parse().ok()),
+            app_minute_remaining: meta.header("X-AppMinLimit-Remaining").and_then(|s| s.parse().ok()),
+            retry_after: meta.header("Retry-After").and_then(|s| s.parse().ok()),
+            problem: meta.header("X-Rate-Limit-Problem").map(|s| s.to_string()),
+        };
+        meta
+    }
+
+    /// The value of the first header named `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter()
+            .find(|&&(ref key, _)| key.eq_ignore_ascii_case(name))
+            .map(|&(_, ref value)| value.as_str())
+    }
+}
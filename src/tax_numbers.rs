@@ -0,0 +1,105 @@
+use std::error;
+use std::fmt;
+
+/// The tax system of an organisation, which decides what a valid `TaxNumber` looks like.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TaxRegion {
+    /// An 11 digit Australian Business Number.
+    Australia,
+    /// An 8 or 9 digit GST (IRD) number.
+    NewZealand,
+    /// A VAT registration number, optionally prefixed with `GB`.
+    UnitedKingdom,
+}
+
+/// A tax number which isn't valid in the organisation's region.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaxNumberError {
+    pub region: TaxRegion,
+    pub tax_number: String,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for TaxNumberError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self.region {
+            TaxRegion::Australia => "ABN",
+            TaxRegion::NewZealand => "GST number",
+            TaxRegion::UnitedKingdom => "VAT number",
+        };
+        write!(f, "invalid {} {:?}: {}", kind, self.tax_number, self.reason)
+    }
+}
+
+impl error::Error for TaxNumberError {
+    fn description(&self) -> &str {
+        "invalid tax number"
+    }
+}
+
+impl TaxRegion {
+    /// Checks the format, and checksum where there is one, of a tax number in this region.
+    ///
+    /// Spaces and hyphens are ignored, as Xero ignores them.
+    pub fn validate(&self, tax_number: &str) -> Result<(), TaxNumberError> {
+        let compact: String = tax_number.chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .flat_map(char::to_uppercase)
+            .collect();
+        let result = match *self {
+            TaxRegion::Australia => validate_abn(&compact),
+            TaxRegion::NewZealand => validate_gst(&compact),
+            TaxRegion::UnitedKingdom => validate_vat(&compact),
+        };
+        result.map_err(|reason| TaxNumberError{region: *self, tax_number: tax_number.to_string(), reason: reason})
+    }
+}
+
+fn digits(s: &str) -> Option<Vec<u32>> {
+    s.chars().map(|c| c.to_digit(10)).collect()
+}
+
+fn validate_abn(abn: &str) -> Result<(), &'static str> {
+    const WEIGHTS: [u32; 11] = [10, 1, 3, 5, 7, 9, 11, 13, 15, 17, 19];
+    let mut digits = match digits(abn) {
+        Some(ref digits) if digits.len() == 11 && digits[0] > 0 => digits.clone(),
+        _ => return Err("must be 11 digits"),
+    };
+    digits[0] -= 1;
+    let sum: u32 = digits.iter().zip(WEIGHTS.iter()).map(|(d, w)| d * w).sum();
+    if sum % 89 == 0 { Ok(()) } else { Err("checksum doesn't match") }
+}
+
+fn validate_gst(gst: &str) -> Result<(), &'static str> {
+    const PRIMARY: [u32; 8] = [3, 2, 7, 6, 5, 4, 3, 2];
+    const SECONDARY: [u32; 8] = [7, 4, 3, 2, 5, 2, 7, 6];
+    let mut digits = match digits(gst) {
+        Some(digits) if digits.len() == 8 || digits.len() == 9 => digits,
+        _ => return Err("must be 8 or 9 digits"),
+    };
+    if digits.len() == 8 {
+        digits.insert(0, 0);
+    }
+    let check = digits[8];
+    let check_digit = |weights: &[u32; 8]| {
+        let remainder = digits[..8].iter().zip(weights.iter()).map(|(d, w)| d * w).sum::<u32>() % 11;
+        if remainder == 0 { 0 } else { 11 - remainder }
+    };
+    let expected = match check_digit(&PRIMARY) {
+        10 => check_digit(&SECONDARY),
+        expected => expected,
+    };
+    if expected == check { Ok(()) } else { Err("checksum doesn't match") }
+}
+
+fn validate_vat(vat: &str) -> Result<(), &'static str> {
+    let number = if vat.starts_with("GB") { &vat[2..] } else { vat };
+    let government = (number.starts_with("GD") || number.starts_with("HA"))
+        && number.len() == 5 && digits(&number[2..]).is_some();
+    let standard = (number.len() == 9 || number.len() == 12) && digits(number).is_some();
+    if government || standard {
+        Ok(())
+    } else {
+        Err("must be 9 or 12 digits, or GD or HA followed by 3 digits")
+    }
+}
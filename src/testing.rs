@@ -0,0 +1,1021 @@
+//! An in-memory fake of the Xero Accounting API, for testing code which uses this crate.
+//!
+//! `FakeXero` is a `Transport` which stores Contacts, ContactGroups, Currencies, Invoices, Items and Payments instead of
+//! sending requests anywhere. It gives out GUIDs, rejects invalid elements with the same
+//! validation errors Xero returns, and moves invoices between statuses as Xero does, so that
+//! workflows can be tested end-to-end without a Xero organisation.
+//!
+//! ```rust,ignore
+//! let xero = FakeXero::new();
+//! let client = xero.client();
+//! let contact = Contact::put(&client, ContactParams::builder("ABC Limited").build(), None)?;
+//! assert_eq!(xero.contacts()?.len(), 1);
+//! ```
+//!
+//! Only available with the `testing` feature.
+
+use application::Application;
+use chrono::{NaiveDate, Utc};
+use client::Client;
+use error::Error;
+use resources::contact_groups::ContactGroup;
+use resources::contacts::Contact;
+use resources::currencies::Currency;
+use resources::invoices::Invoice;
+use resources::items::Item;
+use resources::payments::Payment;
+use serde::de::DeserializeOwned;
+use serde_json as json;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use transport::{Request, Response, Transport};
+use uuid::Uuid;
+use xml::reader::{EventReader, XmlEvent};
+
+type Object = json::Map<String, json::Value>;
+
+/// Fields which Xero returns as numbers rather than strings.
+const NUMBERS: &[&str] = &[
+    "Amount", "AmountCredited", "CurrencyRate", "Day", "DiscountAmount", "DiscountRate", "LineAmount", "Quantity", "TaxAmount", "UnitAmount", "UnitPrice",
+];
+
+/// Fields which Xero returns as booleans rather than strings.
+const BOOLEANS: &[&str] = &[
+    "IncludeInEmails", "IsCustomer", "IsPurchased", "IsReconciled", "IsSold", "IsSupplier", "IsTrackedAsInventory",
+    "SentToContact",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Resource {
+    Contacts,
+    ContactGroups,
+    Currencies,
+    Invoices,
+    Items,
+    Payments,
+}
+
+impl Resource {
+    fn from_collection(name: &str) -> Option<Resource> {
+        match name {
+            "Contacts" => Some(Resource::Contacts),
+            "ContactGroups" => Some(Resource::ContactGroups),
+            "Currencies" => Some(Resource::Currencies),
+            "Invoices" => Some(Resource::Invoices),
+            "Items" => Some(Resource::Items),
+            "Payments" => Some(Resource::Payments),
+            _ => None,
+        }
+    }
+
+    fn collection(&self) -> &'static str {
+        match *self {
+            Resource::Contacts => "Contacts",
+            Resource::ContactGroups => "ContactGroups",
+            Resource::Currencies => "Currencies",
+            Resource::Invoices => "Invoices",
+            Resource::Items => "Items",
+            Resource::Payments => "Payments",
+        }
+    }
+
+    fn id_field(&self) -> &'static str {
+        match *self {
+            Resource::Contacts => "ContactID",
+            Resource::ContactGroups => "ContactGroupID",
+            Resource::Currencies => "Code",
+            Resource::Invoices => "InvoiceID",
+            Resource::Items => "ItemID",
+            Resource::Payments => "PaymentID",
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+struct State {
+    contacts: Vec<Object>,
+    contact_groups: Vec<Object>,
+    currencies: Vec<Object>,
+    invoices: Vec<Object>,
+    items: Vec<Object>,
+    payments: Vec<Object>,
+    invoice_number: u32,
+    /// The history records of each resource, by lowercase ID.
+    history: HashMap<String, Vec<json::Value>>,
+}
+
+/// An in-memory Xero organisation which answers requests as Xero would.
+///
+/// Clones share the same data, so one clone can be given to a `Client` while another is kept to
+/// inspect what the client created.
+#[derive(Clone, Default)]
+pub struct FakeXero {
+    state: Arc<Mutex<State>>,
+}
+
+/// Signs requests to the fake, which doesn't check them.
+struct Unsigned;
+
+impl Application for Unsigned {
+    fn get_signature(&self, _: &str, _: &str) -> Result<String, Error> {
+        Ok(String::from("OAuth fake"))
+    }
+}
+
+impl FakeXero {
+    pub fn new() -> FakeXero {
+        FakeXero::default()
+    }
+
+    /// A client which sends its requests to this fake.
+    pub fn client(&self) -> Client {
+        Client::with_transport(Unsigned, self.clone())
+    }
+
+    pub fn contacts(&self) -> Result<Vec<Contact>, Error> {
+        self.all(Resource::Contacts)
+    }
+
+    pub fn contact_groups(&self) -> Result<Vec<ContactGroup>, Error> {
+        self.all(Resource::ContactGroups)
+    }
+
+    pub fn currencies(&self) -> Result<Vec<Currency>, Error> {
+        self.all(Resource::Currencies)
+    }
+
+    pub fn invoices(&self) -> Result<Vec<Invoice>, Error> {
+        self.all(Resource::Invoices)
+    }
+
+    pub fn items(&self) -> Result<Vec<Item>, Error> {
+        self.all(Resource::Items)
+    }
+
+    pub fn payments(&self) -> Result<Vec<Payment>, Error> {
+        self.all(Resource::Payments)
+    }
+
+    /// Deserializes every stored resource of a type, failing if one no longer matches its struct.
+    fn all<T: DeserializeOwned>(&self, resource: Resource) -> Result<Vec<T>, Error> {
+        self.lock().objects(resource).iter()
+            .map(|object| json::from_value(json::Value::Object(object.clone())).map_err(Error::from))
+            .collect()
+    }
+
+    fn lock(&self) -> MutexGuard<State> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Transport for FakeXero {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        let (path, query) = match request.url.find('?') {
+            Some(i) => (&request.url[..i], &request.url[i + 1..]),
+            None => (&request.url[..], ""),
+        };
+        let path = match path.find("/api.xro/2.0/") {
+            Some(i) => &path[i + "/api.xro/2.0/".len()..],
+            None => return Ok(not_found()),
+        };
+        let mut segments = path.split('/');
+        let resource = match segments.next().and_then(Resource::from_collection) {
+            Some(resource) => resource,
+            None => return Ok(not_found()),
+        };
+        let id = segments.next();
+        let sub_resource = segments.next();
+        let summarize_errors = !query.split('&').any(|param| param == "summarizeErrors=false");
+
+        let mut state = self.lock();
+        if let (Some(id), Some(sub_resource)) = (id, sub_resource) {
+            return Ok(match (request.method.as_str(), sub_resource) {
+                ("POST", "Email") if resource == Resource::Invoices => match state.find(resource, id) {
+                    Some(i) => state.email_invoice(i),
+                    None => not_found(),
+                },
+                ("GET", "OnlineInvoice") if resource == Resource::Invoices => match state.find(resource, id) {
+                    Some(i) => state.online_invoice(i),
+                    None => not_found(),
+                },
+                (method, "Contacts") if resource == Resource::ContactGroups => match state.find(resource, id) {
+                    Some(i) => state.group_members(method, i, segments.next(), &request.body),
+                    None => not_found(),
+                },
+                ("GET", "History") if state.find(resource, id).is_some() => {
+                    let records = state.history.get(&id.to_lowercase()).cloned().unwrap_or_else(Vec::new);
+                    let body = json!({"HistoryRecords": records});
+                    Response{status: 200, headers: json_headers(), body: body.to_string().into_bytes()}
+                }
+                _ => not_found(),
+            });
+        }
+        match (request.method.as_str(), id) {
+            ("GET", None) => {
+                let objects = state.objects(resource).iter()
+                    .filter(|object| matches_query(object, query))
+                    .cloned()
+                    .map(|mut object| {
+                        // Xero only lists a group's members when getting the group itself
+                        if resource == Resource::ContactGroups {
+                            object.remove("Contacts");
+                        }
+                        object
+                    })
+                    .collect();
+                Ok(success(resource, objects))
+            }
+            ("GET", Some(id)) if accepts_pdf(request) => match state.find(resource, id) {
+                Some(i) if resource == Resource::Invoices => {
+                    let number = string(&state.invoices[i], "InvoiceNumber").to_string();
+                    let body = format!("%PDF-1.4\n% FakeXero invoice {}\n%%EOF\n", number).into_bytes();
+                    let headers = vec![(String::from("Content-Type"), String::from("application/pdf"))];
+                    Ok(Response{status: 200, headers: headers, body: body})
+                }
+                _ => Ok(not_found()),
+            },
+            ("GET", Some(id)) => match state.find(resource, id) {
+                Some(i) => {
+                    let mut object = state.objects(resource)[i].clone();
+                    if resource == Resource::Contacts {
+                        object.insert(String::from("ContactGroups"), json::Value::Array(state.groups_of(id)));
+                    }
+                    Ok(success(resource, vec![object]))
+                }
+                None => Ok(not_found()),
+            },
+            ("POST", _) if resource == Resource::Currencies => Ok(not_allowed()),
+            ("PUT", None) | ("POST", None) | ("POST", Some(_)) => {
+                if let Some(id) = id {
+                    if state.find(resource, id).is_none() {
+                        return Ok(not_found());
+                    }
+                }
+                let elements = match parse_elements(&request.body) {
+                    Some(elements) => elements,
+                    None => return Ok(bad_request("The request body could not be read")),
+                };
+                let create = request.method == "PUT";
+                Ok(state.write(resource, elements, id, create, summarize_errors))
+            }
+            ("DELETE", Some(id)) if resource == Resource::Items => match state.find(resource, id) {
+                Some(i) => {
+                    state.items.remove(i);
+                    Ok(Response{status: 204, headers: Vec::new(), body: Vec::new()})
+                }
+                None => Ok(not_found()),
+            },
+            _ => Ok(not_allowed()),
+        }
+    }
+}
+
+impl State {
+    fn objects(&self, resource: Resource) -> &Vec<Object> {
+        match resource {
+            Resource::Contacts => &self.contacts,
+            Resource::ContactGroups => &self.contact_groups,
+            Resource::Currencies => &self.currencies,
+            Resource::Invoices => &self.invoices,
+            Resource::Items => &self.items,
+            Resource::Payments => &self.payments,
+        }
+    }
+
+    fn objects_mut(&mut self, resource: Resource) -> &mut Vec<Object> {
+        match resource {
+            Resource::Contacts => &mut self.contacts,
+            Resource::ContactGroups => &mut self.contact_groups,
+            Resource::Currencies => &mut self.currencies,
+            Resource::Invoices => &mut self.invoices,
+            Resource::Items => &mut self.items,
+            Resource::Payments => &mut self.payments,
+        }
+    }
+
+    fn find(&self, resource: Resource, id: &str) -> Option<usize> {
+        self.objects(resource).iter().position(|object| {
+            object.get(resource.id_field()).and_then(|v| v.as_str()).map_or(false, |v| v.eq_ignore_ascii_case(id))
+        })
+    }
+
+    /// Saves each element, either all-or-nothing or independently if errors aren't summarized.
+    fn write(&mut self, resource: Resource, elements: Vec<Object>, id: Option<&str>, create: bool, summarize_errors: bool) -> Response {
+        let before = self.clone();
+        let mut results = Vec::with_capacity(elements.len());
+        let mut failed = false;
+        for element in elements {
+            let element_id = id.map(|id| id.to_string())
+                .or_else(|| element.get(resource.id_field()).and_then(|v| v.as_str()).map(|v| v.to_string()));
+            let result = match element_id {
+                Some(ref id) if !create => self.update(resource, id, element.clone()),
+                _ => self.create(resource, element.clone()),
+            };
+            failed = failed || result.is_err();
+            results.push((element, result));
+        }
+
+        if !summarize_errors {
+            let objects = results.into_iter().map(|(element, result)| match result {
+                Ok(mut object) => {
+                    object.insert(String::from("StatusAttributeString"), json::Value::from("OK"));
+                    object
+                }
+                Err(errors) => {
+                    let mut element = with_errors(element, errors);
+                    element.insert(String::from("StatusAttributeString"), json::Value::from("ERROR"));
+                    element
+                }
+            }).collect();
+            success(resource, objects)
+        } else if failed {
+            *self = before;
+            let elements: Vec<json::Value> = results.into_iter().map(|(element, result)| {
+                json::Value::Object(with_errors(element, result.err().unwrap_or_else(Vec::new)))
+            }).collect();
+            let body = json!({
+                "ErrorNumber": 10,
+                "Type": "ValidationException",
+                "Message": "A validation exception occurred",
+                "Elements": elements,
+            });
+            Response{status: 400, headers: json_headers(), body: body.to_string().into_bytes()}
+        } else {
+            success(resource, results.into_iter().filter_map(|(_, result)| result.ok()).collect())
+        }
+    }
+
+    fn create(&mut self, resource: Resource, element: Object) -> Result<Object, Vec<String>> {
+        let mut object = Object::new();
+        match resource {
+            Resource::Contacts => {
+                object.insert(String::from("ContactStatus"), json::Value::from("ACTIVE"));
+                object.insert(String::from("Addresses"), json!([]));
+                object.insert(String::from("Phones"), json!([]));
+                object.insert(String::from("IsSupplier"), json::Value::from(false));
+                object.insert(String::from("IsCustomer"), json::Value::from(false));
+            }
+            Resource::ContactGroups => {
+                object.insert(String::from("Status"), json::Value::from("ACTIVE"));
+                object.insert(String::from("Contacts"), json!([]));
+            }
+            // Currencies are identified by their code rather than a GUID
+            Resource::Currencies => return self.save(resource, None, object, element),
+            Resource::Invoices => {
+                object.insert(String::from("Status"), json::Value::from("DRAFT"));
+                object.insert(String::from("LineAmountTypes"), json::Value::from("Exclusive"));
+                object.insert(String::from("LineItems"), json!([]));
+                object.insert(String::from("AmountPaid"), json::Value::from(0.0));
+            }
+            Resource::Items => {
+                object.insert(String::from("IsSold"), json::Value::from(true));
+                object.insert(String::from("IsPurchased"), json::Value::from(true));
+            }
+            Resource::Payments => {
+                object.insert(String::from("Status"), json::Value::from("AUTHORISED"));
+                object.insert(String::from("IsReconciled"), json::Value::from(false));
+            }
+        }
+        object.insert(resource.id_field().to_string(), json::Value::from(Uuid::new_v4().to_string()));
+        self.save(resource, None, object, element)
+    }
+
+    fn update(&mut self, resource: Resource, id: &str, element: Object) -> Result<Object, Vec<String>> {
+        match self.find(resource, id) {
+            Some(i) => {
+                let object = self.objects(resource)[i].clone();
+                self.save(resource, Some(i), object, element)
+            }
+            None => Err(vec![format!("{} could not be found", id)]),
+        }
+    }
+
+    /// Applies `element` to `object`, validating the result before storing it at `index`.
+    fn save(&mut self, resource: Resource, index: Option<usize>, object: Object, element: Object) -> Result<Object, Vec<String>> {
+        let previous_status = string(&object, "ContactStatus").to_string();
+        let object = match resource {
+            Resource::Contacts => self.save_contact(index, object, element),
+            Resource::ContactGroups => self.save_contact_group(index, object, element),
+            Resource::Currencies => self.save_currency(index, object, element),
+            Resource::Invoices => self.save_invoice(index, object, element),
+            Resource::Items => self.save_item(index, object, element),
+            Resource::Payments => self.save_payment(index, object, element),
+        }?;
+        let changes = match index {
+            None => "Created",
+            Some(_) if string(&object, "ContactStatus") != previous_status => match string(&object, "ContactStatus") {
+                "ARCHIVED" => "Archived",
+                _ => "Restored",
+            },
+            Some(_) => "Updated",
+        };
+        let record = json!({"Changes": changes, "DateUTC": now(), "User": "FakeXero", "Details": ""});
+        let id = string(&object, resource.id_field()).to_lowercase();
+        self.history.entry(id).or_default().push(record);
+
+        let objects = self.objects_mut(resource);
+        match index {
+            Some(i) => objects[i] = object.clone(),
+            None => objects.push(object.clone()),
+        }
+        Ok(object)
+    }
+
+    fn save_contact(&mut self, index: Option<usize>, mut contact: Object, element: Object) -> Result<Object, Vec<String>> {
+        merge(&mut contact, element, "ContactID");
+        let name = string(&contact, "Name").to_string();
+        if name.trim().is_empty() {
+            return Err(vec![String::from("The contact name must be specified")]);
+        }
+        let duplicate = self.contacts.iter().enumerate().any(|(i, other)| {
+            Some(i) != index && string(other, "ContactStatus") == "ACTIVE"
+                && string(other, "Name").eq_ignore_ascii_case(&name)
+        });
+        if duplicate && string(&contact, "ContactStatus") == "ACTIVE" {
+            return Err(vec![format!("The contact name {} is already assigned to another contact. \
+                                     The contact name must be unique across all active contacts.", name)]);
+        }
+        contact.insert(String::from("UpdatedDateUTC"), json::Value::from(now()));
+        Ok(contact)
+    }
+
+    fn save_contact_group(&mut self, index: Option<usize>, mut group: Object, element: Object) -> Result<Object, Vec<String>> {
+        merge(&mut group, element, "ContactGroupID");
+        let name = string(&group, "Name").trim().to_string();
+        let status = string(&group, "Status").to_string();
+        let mut errors = Vec::new();
+        if name.is_empty() {
+            errors.push(String::from("The contact group name must be specified"));
+        }
+        if status != "ACTIVE" && status != "DELETED" {
+            errors.push(format!("{} is not a valid contact group status", status));
+        }
+        let duplicate = self.contact_groups.iter().enumerate().any(|(i, other)| {
+            Some(i) != index && string(other, "Status") == "ACTIVE" && string(other, "Name").eq_ignore_ascii_case(&name)
+        });
+        if duplicate && status == "ACTIVE" {
+            errors.push(format!("A contact group with the name {} already exists", name));
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(group)
+    }
+
+    /// Adds or removes the members of a contact group, for `/ContactGroups/{id}/Contacts`.
+    fn group_members(&mut self, method: &str, group: usize, contact_id: Option<&str>, body: &[u8]) -> Response {
+        let mut members = self.contact_groups[group].get("Contacts").and_then(|c| c.as_array()).cloned().unwrap_or_else(Vec::new);
+        let is_member = |member: &json::Value, id: &str| member.get("ContactID").and_then(|v| v.as_str()).map_or(false, |v| v.eq_ignore_ascii_case(id));
+        let response = match (method, contact_id) {
+            ("PUT", None) => {
+                let elements = match parse_elements(body) {
+                    Some(elements) => elements,
+                    None => return bad_request("The request body could not be read"),
+                };
+                let mut added = Vec::new();
+                for element in elements {
+                    let contact = match self.find(Resource::Contacts, string(&element, "ContactID")) {
+                        Some(i) => &self.contacts[i],
+                        None => return bad_request("A valid ContactID must be specified for each contact"),
+                    };
+                    let member = json!({"ContactID": string(contact, "ContactID"), "Name": string(contact, "Name")});
+                    if !members.iter().any(|m| is_member(m, string(contact, "ContactID"))) {
+                        members.push(member.clone());
+                    }
+                    added.push(member);
+                }
+                let body = json!({"Contacts": added});
+                Response{status: 200, headers: json_headers(), body: body.to_string().into_bytes()}
+            }
+            ("DELETE", Some(id)) => {
+                if !members.iter().any(|m| is_member(m, id)) {
+                    return not_found();
+                }
+                members.retain(|m| !is_member(m, id));
+                Response{status: 204, headers: Vec::new(), body: Vec::new()}
+            }
+            ("DELETE", None) => {
+                members.clear();
+                Response{status: 204, headers: Vec::new(), body: Vec::new()}
+            }
+            _ => return not_allowed(),
+        };
+        self.contact_groups[group].insert(String::from("Contacts"), json::Value::Array(members));
+        response
+    }
+
+    fn email_invoice(&mut self, i: usize) -> Response {
+        let status = string(&self.invoices[i], "Status").to_string();
+        if string(&self.invoices[i], "Type") != "ACCREC" {
+            return validation_error("Invalid invoice type. Only sales invoices can be emailed");
+        }
+        if status != "SUBMITTED" && status != "AUTHORISED" && status != "PAID" {
+            return validation_error("Invoice not of valid status for sending by email");
+        }
+        let contact_id = self.invoices[i].get("Contact").and_then(|c| c.as_object()).map_or("", |c| string(c, "ContactID")).to_string();
+        let has_email = self.find(Resource::Contacts, &contact_id)
+            .map_or(false, |c| !string(&self.contacts[c], "EmailAddress").is_empty());
+        if !has_email {
+            return validation_error("The contact does not have an email address");
+        }
+        self.invoices[i].insert(String::from("SentToContact"), json::Value::from(true));
+        Response{status: 204, headers: Vec::new(), body: Vec::new()}
+    }
+
+    fn online_invoice(&self, i: usize) -> Response {
+        let invoice = &self.invoices[i];
+        if string(invoice, "Type") != "ACCREC" {
+            return validation_error("Invalid invoice type. Online invoices are only available for Accounts Receivable invoices");
+        }
+        match string(invoice, "Status") {
+            "DRAFT" | "DELETED" | "VOIDED" => validation_error("Invalid invoice status. Online invoices are not available for this invoice"),
+            _ => {
+                let url = format!("https://in.xero.com/{}", string(invoice, "InvoiceID"));
+                let body = json!({"OnlineInvoices": [{"OnlineInvoiceUrl": url}]});
+                Response{status: 200, headers: json_headers(), body: body.to_string().into_bytes()}
+            }
+        }
+    }
+
+    /// The active groups a contact belongs to, as Xero includes them when getting the contact.
+    fn groups_of(&self, contact_id: &str) -> Vec<json::Value> {
+        self.contact_groups.iter()
+            .filter(|group| string(group, "Status") == "ACTIVE")
+            .filter(|group| group.get("Contacts").and_then(|c| c.as_array()).map_or(false, |members| {
+                members.iter().any(|m| m.get("ContactID").and_then(|v| v.as_str()).map_or(false, |v| v.eq_ignore_ascii_case(contact_id)))
+            }))
+            .map(|group| json!({
+                "ContactGroupID": string(group, "ContactGroupID"),
+                "Name": string(group, "Name"),
+                "Status": "ACTIVE",
+            }))
+            .collect()
+    }
+
+    fn save_item(&mut self, index: Option<usize>, mut item: Object, element: Object) -> Result<Object, Vec<String>> {
+        merge(&mut item, element, "ItemID");
+        let code = string(&item, "Code").to_string();
+        let mut errors = Vec::new();
+        if code.is_empty() {
+            errors.push(String::from("Item code must be specified"));
+        } else if code.chars().count() > 30 {
+            errors.push(String::from("The Item code must not be more than 30 characters long"));
+        } else if self.items.iter().enumerate().any(|(i, other)| Some(i) != index && string(other, "Code").eq_ignore_ascii_case(&code)) {
+            errors.push(format!("Item code '{}' already exists", code));
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let tracked = item.contains_key("InventoryAssetAccountCode");
+        item.insert(String::from("IsTrackedAsInventory"), json::Value::from(tracked));
+        item.insert(String::from("UpdatedDateUTC"), json::Value::from(now()));
+        Ok(item)
+    }
+
+    fn save_currency(&mut self, index: Option<usize>, mut currency: Object, element: Object) -> Result<Object, Vec<String>> {
+        // Merge every field, as the code is both the ID and the content of a currency
+        merge(&mut currency, element, "");
+        let code = string(&currency, "Code").to_string();
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_uppercase()) {
+            return Err(vec![format!("{} is not a valid currency code", code)]);
+        }
+        if self.currencies.iter().enumerate().any(|(i, other)| Some(i) != index && string(other, "Code") == code) {
+            return Err(vec![format!("The currency {} has already been added", code)]);
+        }
+        if string(&currency, "Description").is_empty() {
+            let description = match code.as_str() {
+                "AUD" => "Australian Dollar",
+                "EUR" => "Euro",
+                "GBP" => "British Pound",
+                "NZD" => "New Zealand Dollar",
+                "USD" => "United States Dollar",
+                _ => code.as_str(),
+            };
+            currency.insert(String::from("Description"), json::Value::from(description));
+        }
+        Ok(currency)
+    }
+
+    fn save_invoice(&mut self, index: Option<usize>, mut invoice: Object, element: Object) -> Result<Object, Vec<String>> {
+        let previous = string(&invoice, "Status").to_string();
+        if index.is_some() && (previous == "PAID" || previous == "VOIDED" || previous == "DELETED") {
+            return Err(vec![format!("This document cannot be edited as it has a status of {}", previous)]);
+        }
+        merge(&mut invoice, element, "InvoiceID");
+        let mut errors = Vec::new();
+
+        let invoice_type = string(&invoice, "Type").to_string();
+        if invoice_type != "ACCREC" && invoice_type != "ACCPAY" {
+            errors.push(String::from("Invoice Type must be specified as ACCREC or ACCPAY"));
+        }
+
+        let contact = invoice.get("Contact").and_then(|c| c.as_object()).and_then(|c| {
+            let id = string(c, "ContactID");
+            let name = string(c, "Name");
+            self.contacts.iter().find(|other| {
+                (!id.is_empty() && string(other, "ContactID").eq_ignore_ascii_case(id))
+                    || (id.is_empty() && !name.is_empty() && string(other, "Name").eq_ignore_ascii_case(name))
+            })
+        }).map(|c| json!({"ContactID": string(c, "ContactID"), "Name": string(c, "Name")}));
+        match contact {
+            Some(contact) => { invoice.insert(String::from("Contact"), contact); }
+            None => errors.push(String::from("A valid Contact must be specified")),
+        }
+
+        let status = string(&invoice, "Status").to_string();
+        if index.is_some() && status != previous && !invoice_transition(&previous, &status) {
+            errors.push(format!("Invoice cannot be changed from {} to {}", previous, status));
+        }
+        if index.is_none() && status != "DRAFT" && status != "SUBMITTED" && status != "AUTHORISED" {
+            errors.push(format!("Invoices cannot be created with a status of {}", status));
+        }
+        if status == "VOIDED" && number(&invoice, "AmountPaid") > 0.0 {
+            errors.push(String::from("This document cannot be voided as it has a payment allocated to it"));
+        }
+
+        for date in &["Date", "DueDate"] {
+            let value = string(&invoice, date).to_string();
+            if value.is_empty() {
+                continue;
+            }
+            let day = if value.len() > 10 { &value[..10] } else { &value[..] };
+            match NaiveDate::parse_from_str(day, "%Y-%m-%d") {
+                Ok(parsed) => {
+                    let field = if *date == "Date" { "DateString" } else { "DueDateString" };
+                    invoice.insert(field.to_string(), json::Value::from(parsed.and_hms(0, 0, 0).format("%Y-%m-%dT%H:%M:%S").to_string()));
+                }
+                Err(_) => errors.push(format!("{} is not a valid date", date)),
+            }
+        }
+        if !invoice.contains_key("DateString") {
+            let today = Utc::today().naive_utc().and_hms(0, 0, 0);
+            invoice.insert(String::from("DateString"), json::Value::from(today.format("%Y-%m-%dT%H:%M:%S").to_string()));
+        }
+
+        let currency_code = string(&invoice, "CurrencyCode").to_string();
+        if !currency_code.is_empty() && !self.currencies.iter().any(|currency| string(currency, "Code") == currency_code) {
+            errors.push(format!("The currency {} has not been added to this organisation", currency_code));
+        }
+        if !invoice.contains_key("CurrencyRate") {
+            // The fake has no daily exchange rates to fall back on
+            invoice.insert(String::from("CurrencyRate"), json::Value::from(1.0));
+        }
+
+        let line_items = invoice.get("LineItems").and_then(|l| l.as_array()).cloned().unwrap_or_else(Vec::new);
+        if line_items.is_empty() && (status == "SUBMITTED" || status == "AUTHORISED") {
+            errors.push(String::from("At least one line item must be specified"));
+        }
+        if line_items.iter().any(|l| l.get("Description").and_then(|d| d.as_str()).map_or(true, |d| d.is_empty())) {
+            errors.push(String::from("Description must be specified for each line item"));
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let inclusive = string(&invoice, "LineAmountTypes") == "Inclusive";
+        let no_tax = string(&invoice, "LineAmountTypes") == "NoTax";
+        let (mut line_total, mut total_tax) = (0.0, 0.0);
+        let line_items: Vec<json::Value> = line_items.into_iter().map(|line| {
+            let mut line = line.as_object().cloned().unwrap_or_else(Object::new);
+            let quantity = line.get("Quantity").and_then(|q| q.as_f64()).unwrap_or(1.0);
+            let unit_amount = number(&line, "UnitAmount");
+            let discount = number(&line, "DiscountRate");
+            let amount = line.get("LineAmount").and_then(|a| a.as_f64())
+                .unwrap_or_else(|| round(quantity * unit_amount * (1.0 - discount / 100.0)));
+            let tax = if no_tax { 0.0 } else { number(&line, "TaxAmount") };
+            if string(&line, "LineItemID").is_empty() {
+                line.insert(String::from("LineItemID"), json::Value::from(Uuid::new_v4().to_string()));
+            }
+            line.insert(String::from("Quantity"), json::Value::from(quantity));
+            line.insert(String::from("LineAmount"), json::Value::from(amount));
+            line.insert(String::from("TaxAmount"), json::Value::from(tax));
+            line_total += amount;
+            total_tax += tax;
+            json::Value::Object(line)
+        }).collect();
+        let (subtotal, total) = if inclusive {
+            (round(line_total - total_tax), round(line_total))
+        } else {
+            (round(line_total), round(line_total + total_tax))
+        };
+        invoice.insert(String::from("LineItems"), json::Value::Array(line_items));
+        invoice.insert(String::from("SubTotal"), json::Value::from(subtotal));
+        invoice.insert(String::from("TotalTax"), json::Value::from(round(total_tax)));
+        invoice.insert(String::from("Total"), json::Value::from(total));
+        let amount_due = round(total - number(&invoice, "AmountPaid"));
+        invoice.insert(String::from("AmountDue"), json::Value::from(amount_due));
+
+        if string(&invoice, "InvoiceNumber").is_empty() {
+            let invoice_number = if invoice_type == "ACCREC" {
+                self.invoice_number += 1;
+                format!("INV-{:04}", self.invoice_number)
+            } else {
+                String::new()
+            };
+            invoice.insert(String::from("InvoiceNumber"), json::Value::from(invoice_number));
+        }
+        invoice.insert(String::from("UpdatedDateUTC"), json::Value::from(now()));
+        Ok(invoice)
+    }
+
+    fn save_payment(&mut self, index: Option<usize>, mut payment: Object, element: Object) -> Result<Object, Vec<String>> {
+        if index.is_some() {
+            // Payments can only be deleted, which returns the amount to the invoice
+            if string(&element, "Status") != "DELETED" || element.len() != 1 {
+                return Err(vec![String::from("Payments can only be deleted")]);
+            }
+            if string(&payment, "Status") == "DELETED" {
+                return Err(vec![String::from("This payment has already been deleted")]);
+            }
+            let amount = number(&payment, "Amount");
+            let invoice_id = payment.get("Invoice").and_then(|i| i.as_object()).map(|i| string(i, "InvoiceID").to_string());
+            if let Some(i) = invoice_id.and_then(|id| self.find(Resource::Invoices, &id)) {
+                let invoice = &mut self.invoices[i];
+                let paid = round(number(invoice, "AmountPaid") - amount);
+                let due = round(number(invoice, "AmountDue") + amount);
+                invoice.insert(String::from("AmountPaid"), json::Value::from(paid));
+                invoice.insert(String::from("AmountDue"), json::Value::from(due));
+                invoice.insert(String::from("Status"), json::Value::from("AUTHORISED"));
+                invoice.remove("FullyPaidOnDate");
+                let payment_id = string(&payment, "PaymentID").to_string();
+                if let Some(payments) = invoice.get_mut("Payments").and_then(|p| p.as_array_mut()) {
+                    payments.retain(|p| p.get("PaymentID").and_then(|id| id.as_str()) != Some(payment_id.as_str()));
+                }
+            }
+            payment.insert(String::from("Status"), json::Value::from("DELETED"));
+            return Ok(payment);
+        }
+
+        merge(&mut payment, element, "PaymentID");
+        let mut errors = Vec::new();
+        if !payment.contains_key("Account") {
+            errors.push(String::from("An Account must be specified"));
+        }
+        let amount = number(&payment, "Amount");
+        if amount <= 0.0 {
+            errors.push(String::from("Payment amount must be greater than zero"));
+        }
+        let target = payment.get("Invoice").and_then(|i| i.as_object()).cloned();
+        let position = target.as_ref().and_then(|target| {
+            let id = string(target, "InvoiceID");
+            let invoice_number = string(target, "InvoiceNumber");
+            self.invoices.iter().position(|invoice| {
+                (!id.is_empty() && string(invoice, "InvoiceID").eq_ignore_ascii_case(id))
+                    || (id.is_empty() && !invoice_number.is_empty() && string(invoice, "InvoiceNumber") == invoice_number)
+            })
+        });
+        let i = match position {
+            Some(i) => i,
+            None => {
+                errors.push(String::from(if target.is_some() {
+                    "Invoice could not be found"
+                } else {
+                    "The fake only supports payments against invoices"
+                }));
+                return Err(errors);
+            }
+        };
+
+        if string(&self.invoices[i], "Status") != "AUTHORISED" {
+            errors.push(String::from("Invoice not of valid status for creating payment"));
+        } else if amount > number(&self.invoices[i], "AmountDue") + 0.005 {
+            errors.push(String::from("Payment amount exceeds the amount outstanding on this document"));
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let invoice = &mut self.invoices[i];
+        let paid = round(number(invoice, "AmountPaid") + amount);
+        let due = round(number(invoice, "AmountDue") - amount);
+        invoice.insert(String::from("AmountPaid"), json::Value::from(paid));
+        invoice.insert(String::from("AmountDue"), json::Value::from(due));
+        let date = NaiveDate::parse_from_str(string(&payment, "Date"), "%Y-%m-%d")
+            .map(|date| format!("/Date({}+0000)/", date.and_hms(0, 0, 0).timestamp() * 1000))
+            .unwrap_or_else(|_| now());
+        if due <= 0.0 {
+            invoice.insert(String::from("Status"), json::Value::from("PAID"));
+            invoice.insert(String::from("FullyPaidOnDate"), json::Value::from(date.clone()));
+        }
+        let applied = json!({
+            "PaymentID": string(&payment, "PaymentID"),
+            "Date": date,
+            "Amount": amount,
+            "Reference": string(&payment, "Reference"),
+            "CurrencyRate": invoice.get("CurrencyRate").cloned().unwrap_or(json::Value::from(1.0)),
+        });
+        if let Some(payments) = invoice.entry(String::from("Payments")).or_insert_with(|| json!([])).as_array_mut() {
+            payments.push(applied);
+        }
+        let payment_type = if string(invoice, "Type") == "ACCPAY" { "ACCPAYPAYMENT" } else { "ACCRECPAYMENT" };
+        payment.insert(String::from("PaymentType"), json::Value::from(payment_type));
+        if !payment.contains_key("CurrencyRate") {
+            payment.insert(String::from("CurrencyRate"), invoice.get("CurrencyRate").cloned().unwrap_or(json::Value::from(1.0)));
+        }
+        payment.insert(String::from("Invoice"), json!({
+            "InvoiceID": string(invoice, "InvoiceID"),
+            "InvoiceNumber": string(invoice, "InvoiceNumber"),
+            "Type": string(invoice, "Type"),
+            "Contact": invoice.get("Contact").cloned().unwrap_or(json::Value::Null),
+        }));
+        payment.insert(String::from("UpdatedDateUTC"), json::Value::from(now()));
+        Ok(payment)
+    }
+}
+
+/// Whether Xero allows an invoice's status to change from `from` to `to`.
+///
+/// Invoices become `PAID` when payments are applied, never by being updated.
+fn invoice_transition(from: &str, to: &str) -> bool {
+    matches!((from, to),
+        ("DRAFT", "SUBMITTED") | ("DRAFT", "AUTHORISED") | ("DRAFT", "DELETED")
+            | ("SUBMITTED", "DRAFT") | ("SUBMITTED", "AUTHORISED") | ("SUBMITTED", "DELETED")
+            | ("AUTHORISED", "VOIDED"))
+}
+
+/// Copies the fields of `element` onto `object`, except for its ID.
+fn merge(object: &mut Object, element: Object, id_field: &str) {
+    for (key, value) in element {
+        if key != id_field {
+            object.insert(key, value);
+        }
+    }
+}
+
+fn accepts_pdf(request: &Request) -> bool {
+    request.headers.iter().any(|(name, value)| name.eq_ignore_ascii_case("Accept") && value == "application/pdf")
+}
+
+/// Whether `object` matches the `ContactIDs` and `Statuses` filters in a query string.
+fn matches_query(object: &Object, query: &str) -> bool {
+    query.split('&').all(|param| {
+        let mut parts = param.splitn(2, '=');
+        let (name, values) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+        let value = match name {
+            "ContactIDs" => object.get("Contact").and_then(|c| c.as_object()).map_or("", |c| string(c, "ContactID")),
+            "Statuses" => string(object, "Status"),
+            _ => return true,
+        };
+        values.split(',').any(|v| v.eq_ignore_ascii_case(value))
+    })
+}
+
+fn with_errors(mut element: Object, errors: Vec<String>) -> Object {
+    if !errors.is_empty() {
+        let messages: Vec<json::Value> = errors.into_iter().map(|message| json!({"Message": message})).collect();
+        element.insert(String::from("HasValidationErrors"), json::Value::from(true));
+        element.insert(String::from("ValidationErrors"), json::Value::Array(messages));
+    }
+    element
+}
+
+fn string<'a>(object: &'a Object, field: &str) -> &'a str {
+    object.get(field).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+fn number(object: &Object, field: &str) -> f64 {
+    object.get(field).and_then(|v| v.as_f64()).unwrap_or(0.0)
+}
+
+fn round(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// The current time in the `/Date(...)/` format Xero uses in JSON.
+fn now() -> String {
+    let now = Utc::now();
+    format!("/Date({}+0000)/", now.timestamp() * 1000 + now.timestamp_subsec_millis() as i64)
+}
+
+fn json_headers() -> Vec<(String, String)> {
+    vec![(String::from("Content-Type"), String::from("application/json; charset=utf-8"))]
+}
+
+fn success(resource: Resource, objects: Vec<Object>) -> Response {
+    let mut body = Object::new();
+    body.insert(String::from("Id"), json::Value::from(Uuid::new_v4().to_string()));
+    body.insert(String::from("Status"), json::Value::from("OK"));
+    body.insert(String::from("ProviderName"), json::Value::from("FakeXero"));
+    body.insert(String::from("DateTimeUTC"), json::Value::from(now()));
+    body.insert(resource.collection().to_string(), json::Value::Array(objects.into_iter().map(json::Value::Object).collect()));
+    Response{status: 200, headers: json_headers(), body: json::Value::Object(body).to_string().into_bytes()}
+}
+
+fn not_found() -> Response {
+    Response{status: 404, headers: Vec::new(), body: b"The resource you're looking for cannot be found".to_vec()}
+}
+
+/// A validation error for a request which isn't about any one element.
+fn validation_error(message: &str) -> Response {
+    let body = json!({
+        "ErrorNumber": 10,
+        "Type": "ValidationException",
+        "Message": "A validation exception occurred",
+        "Elements": [{"ValidationErrors": [{"Message": message}]}],
+    });
+    Response{status: 400, headers: json_headers(), body: body.to_string().into_bytes()}
+}
+
+fn not_allowed() -> Response {
+    Response{status: 405, headers: Vec::new(), body: b"The requested method is not supported by this resource".to_vec()}
+}
+
+fn bad_request(message: &str) -> Response {
+    let body = json!({"ErrorNumber": 14, "Type": "PostDataInvalidException", "Message": message});
+    Response{status: 400, headers: json_headers(), body: body.to_string().into_bytes()}
+}
+
+/// An XML element parsed from a request body.
+struct Element {
+    name: String,
+    text: String,
+    children: Vec<Element>,
+}
+
+impl Element {
+    /// Converts the element to the JSON Xero would return for it.
+    ///
+    /// An element whose children are all named like its singular, e.g. `<Phones><Phone>`, is
+    /// a list; other elements with children are objects.
+    fn into_json(self) -> json::Value {
+        if self.children.is_empty() {
+            let text = self.text.trim().to_string();
+            if NUMBERS.contains(&self.name.as_str()) {
+                if let Ok(number) = text.parse::<f64>() {
+                    return json::Value::from(number);
+                }
+            }
+            if BOOLEANS.contains(&self.name.as_str()) {
+                if let Ok(boolean) = text.parse::<bool>() {
+                    return json::Value::from(boolean);
+                }
+            }
+            if is_list(&self.name) && text.is_empty() {
+                return json!([]);
+            }
+            return json::Value::from(text);
+        }
+
+        let name = self.name;
+        if self.children.iter().all(|child| is_list_of(&name, &child.name)) {
+            json::Value::Array(self.children.into_iter().map(Element::into_json).collect())
+        } else {
+            let mut object = Object::new();
+            for child in self.children {
+                let key = child.name.clone();
+                object.insert(key, child.into_json());
+            }
+            json::Value::Object(object)
+        }
+    }
+}
+
+fn is_list(name: &str) -> bool {
+    matches!(name, "Addresses" | "Phones" | "ContactPersons" | "SalesTrackingCategories"
+        | "PurchasesTrackingCategories" | "LineItems" | "Tracking")
+}
+
+fn is_list_of(list: &str, element: &str) -> bool {
+    if list == "Tracking" {
+        return element == "TrackingCategory";
+    }
+    if element.ends_with('y') && list.len() > element.len() && list.starts_with(&element[..element.len() - 1]) {
+        return &list[element.len() - 1..] == "ies";
+    }
+    list.len() > element.len() && list.starts_with(element) && (&list[element.len()..] == "s" || &list[element.len()..] == "es")
+}
+
+/// Parses a request body into the elements being written, e.g. each `<Contact>` in `<Contacts>`.
+fn parse_elements(body: &[u8]) -> Option<Vec<Object>> {
+    let mut stack: Vec<Element> = Vec::new();
+    let mut root = None;
+    for event in EventReader::new(body) {
+        match event {
+            Ok(XmlEvent::StartElement{name, ..}) => {
+                stack.push(Element{name: name.local_name, text: String::new(), children: Vec::new()});
+            }
+            Ok(XmlEvent::Characters(text)) | Ok(XmlEvent::CData(text)) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&text);
+                }
+            }
+            Ok(XmlEvent::EndElement{..}) => {
+                let element = stack.pop()?;
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => root = Some(element),
+                }
+            }
+            Ok(_) => {}
+            Err(_) => return None,
+        }
+    }
+
+    let root = root?;
+    let list = Resource::from_collection(&root.name).is_some();
+    let elements = if list { root.children } else { vec![root] };
+    elements.into_iter().map(|element| match element.into_json() {
+        json::Value::Object(object) => Some(object),
+        _ => Some(Object::new()),
+    }).collect()
+}
@@ -0,0 +1,244 @@
+use error::Error;
+use hyper;
+use hyper::client::ProxyConfig;
+use hyper::header::Headers;
+use hyper::method::Method;
+use hyper::net::{HttpConnector, HttpStream, HttpsConnector, NetworkConnector};
+use hyper_openssl::OpensslClient;
+use openssl::pkcs12::Pkcs12;
+use openssl::ssl::{SslConnectorBuilder, SslMethod};
+use openssl::x509::X509_FILETYPE_PEM;
+use std::io::{self, Read};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// An HTTP request to the Xero API.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// An HTTP response from the Xero API.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// An HTTP response whose body is read as it arrives, e.g. a large PDF.
+pub struct StreamingResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Box<Read + Send>,
+}
+
+impl From<Response> for StreamingResponse {
+    fn from(response: Response) -> StreamingResponse {
+        StreamingResponse{
+            status: response.status,
+            headers: response.headers,
+            body: Box::new(io::Cursor::new(response.body)),
+        }
+    }
+}
+
+/// Sends requests on behalf of a `Client`.
+///
+/// The default transport sends requests over HTTPS with hyper; other transports can record or
+/// replay requests, or answer them without a network.
+pub trait Transport: Send + Sync {
+    fn send(&self, request: &Request) -> Result<Response, Error>;
+
+    /// Sends a request without waiting for the whole response body.
+    ///
+    /// By default the body is read by `send` first, which suits transports that don't use a
+    /// network.
+    fn send_streaming(&self, request: &Request) -> Result<StreamingResponse, Error> {
+        self.send(request).map(StreamingResponse::from)
+    }
+}
+
+impl<T: Transport + ?Sized> Transport for Arc<T> {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        (**self).send(request)
+    }
+
+    fn send_streaming(&self, request: &Request) -> Result<StreamingResponse, Error> {
+        (**self).send_streaming(request)
+    }
+}
+
+/// Sends requests to Xero over HTTPS.
+pub struct HyperTransport {
+    client: hyper::Client,
+}
+
+impl HyperTransport {
+    /// A transport with default settings: no timeouts, no proxy and the system's CA certificates.
+    pub fn new() -> Result<HyperTransport, Error> {
+        HyperTransport::builder().build()
+    }
+
+    pub fn builder() -> HyperTransportBuilder {
+        HyperTransportBuilder::default()
+    }
+}
+
+#[derive(Clone, Debug)]
+enum ClientCertificate {
+    Pem{certificate: PathBuf, private_key: PathBuf},
+    Pkcs12{der: Vec<u8>, password: String},
+}
+
+/// Configures timeouts, a proxy and TLS for a `HyperTransport`.
+#[derive(Clone, Debug, Default)]
+pub struct HyperTransportBuilder {
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    write_timeout: Option<Duration>,
+    proxy: Option<(String, u16)>,
+    ca_files: Vec<PathBuf>,
+    certificate: Option<ClientCertificate>,
+}
+
+impl HyperTransportBuilder {
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    pub fn write_timeout(mut self, timeout: Duration) -> Self {
+        self.write_timeout = Some(timeout);
+        self
+    }
+
+    /// Sends requests through an HTTP proxy, tunnelling HTTPS with `CONNECT`.
+    pub fn proxy<S: Into<String>>(mut self, host: S, port: u16) -> Self {
+        self.proxy = Some((host.into(), port));
+        self
+    }
+
+    /// Trusts the CA certificates in a PEM file, as well as the system's.
+    pub fn ca_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.ca_files.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Presents a client certificate from PEM files; `certificate` may include its chain.
+    pub fn client_certificate<P: AsRef<Path>, K: AsRef<Path>>(mut self, certificate: P, private_key: K) -> Self {
+        self.certificate = Some(ClientCertificate::Pem{
+            certificate: certificate.as_ref().to_path_buf(),
+            private_key: private_key.as_ref().to_path_buf(),
+        });
+        self
+    }
+
+    /// Presents a client certificate from a PKCS #12 (`.p12` or `.pfx`) archive.
+    ///
+    /// Partner applications use this with their Entrust certificate, together with
+    /// `Client::with_base_url(PARTNER_BASE_URL)`.
+    pub fn client_pkcs12<S: Into<String>>(mut self, der: &[u8], password: S) -> Self {
+        self.certificate = Some(ClientCertificate::Pkcs12{der: der.to_vec(), password: password.into()});
+        self
+    }
+
+    pub fn build(self) -> Result<HyperTransport, Error> {
+        let mut tls = SslConnectorBuilder::new(SslMethod::tls())?;
+        {
+            let context = tls.builder_mut();
+            for ca_file in &self.ca_files {
+                context.set_ca_file(ca_file)?;
+            }
+            match self.certificate {
+                Some(ClientCertificate::Pem{ref certificate, ref private_key}) => {
+                    context.set_certificate_chain_file(certificate)?;
+                    context.set_private_key_file(private_key, X509_FILETYPE_PEM)?;
+                    context.check_private_key()?;
+                }
+                Some(ClientCertificate::Pkcs12{ref der, ref password}) => {
+                    let pkcs12 = Pkcs12::from_der(der)?.parse(password)?;
+                    context.set_certificate(&pkcs12.cert)?;
+                    context.set_private_key(&pkcs12.pkey)?;
+                    for certificate in pkcs12.chain {
+                        context.add_extra_chain_cert(certificate)?;
+                    }
+                    context.check_private_key()?;
+                }
+                None => {}
+            }
+        }
+        let ssl = OpensslClient::from(tls.build());
+        let connector = TimeoutConnector{timeout: self.connect_timeout};
+
+        let mut client = match self.proxy {
+            Some((host, port)) => hyper::Client::with_proxy_config(ProxyConfig::new("http", host, port, connector, ssl)),
+            None => hyper::Client::with_connector(HttpsConnector::with_connector(ssl, connector)),
+        };
+        client.set_read_timeout(self.read_timeout);
+        client.set_write_timeout(self.write_timeout);
+        Ok(HyperTransport{client: client})
+    }
+}
+
+/// Opens TCP connections, giving up on each address after `timeout`.
+struct TimeoutConnector {
+    timeout: Option<Duration>,
+}
+
+impl NetworkConnector for TimeoutConnector {
+    type Stream = HttpStream;
+
+    fn connect(&self, host: &str, port: u16, scheme: &str) -> hyper::Result<HttpStream> {
+        let timeout = match self.timeout {
+            Some(timeout) => timeout,
+            None => return HttpConnector.connect(host, port, scheme),
+        };
+        let mut error = io::Error::new(io::ErrorKind::InvalidInput, format!("could not resolve {}", host));
+        for address in (host, port).to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, timeout) {
+                Ok(stream) => return Ok(HttpStream(stream)),
+                Err(err) => error = err,
+            }
+        }
+        Err(error.into())
+    }
+}
+
+impl Transport for HyperTransport {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        let mut response = self.send_streaming(request)?;
+        let mut body = Vec::with_capacity(4096);
+        response.body.read_to_end(&mut body)?;
+        Ok(Response{status: response.status, headers: response.headers, body: body})
+    }
+
+    fn send_streaming(&self, request: &Request) -> Result<StreamingResponse, Error> {
+        let method: Method = request.method.parse()?;
+        let mut headers = Headers::new();
+        for &(ref name, ref value) in &request.headers {
+            headers.append_raw(name.clone(), value.as_bytes().to_vec());
+        }
+
+        let mut builder = self.client.request(method, &request.url).headers(headers);
+        if !request.body.is_empty() {
+            builder = builder.body(request.body.as_slice());
+        }
+        let response = builder.send()?;
+        Ok(StreamingResponse{
+            status: response.status_raw().0,
+            headers: response.headers.iter().map(|h| (h.name().to_string(), h.value_string())).collect(),
+            body: Box::new(response),
+        })
+    }
+}
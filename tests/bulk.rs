@@ -0,0 +1,71 @@
+extern crate xero;
+
+mod common;
+
+use common::Script;
+use xero::accounting::*;
+
+fn item(code: &str) -> String {
+    format!(r#"{{"ItemID":"0f3c7c5a-1d7c-4a4f-9bb0-6f0e4b2a{:04}","Code":"{}","IsSold":true,"IsPurchased":true,"IsTrackedAsInventory":false,"StatusAttributeString":"OK"}}"#, code.len(), code)
+}
+
+#[test]
+fn put_each_aligns_results_with_elements() {
+    let warned = item("BOLT").replace(r#""StatusAttributeString""#, r#""Warnings":[{"Message":"Only the first 4000 characters of the description were saved"}],"StatusAttributeString""#);
+    let failed = r#"{"Code":"BOLT","StatusAttributeString":"ERROR","HasValidationErrors":true,"ValidationErrors":[{"Message":"Item code 'BOLT' already exists"}]}"#;
+    let body = format!(r#"{{"Items":[{},{},{}]}}"#, warned, failed, item("NUT"));
+    let client = Script::new(vec![body]).client();
+
+    let params = vec![ItemParams::builder("BOLT").build(), ItemParams::builder("BOLT").build(), ItemParams::builder("NUT").build()];
+    let results = Items::put_each(&client, params, None).unwrap();
+
+    let first = results[0].as_ref().unwrap();
+    assert_eq!((first.index, first.resource.code.as_str()), (0, "BOLT"));
+    assert_eq!(first.warnings[0].message, "Only the first 4000 characters of the description were saved");
+    let error = results[1].as_ref().err().unwrap();
+    assert_eq!(error.index, 1);
+    assert_eq!(error.validation_errors[0].message, "Item code 'BOLT' already exists");
+    let last = results[2].as_ref().unwrap();
+    assert_eq!((last.index, last.resource.code.as_str()), (2, "NUT"));
+    assert!(last.warnings.is_empty());
+}
+
+#[test]
+fn bulk_writer_splits_batches() {
+    let client = Script::default().client();
+    let codes = |batches: Vec<Vec<ItemParams>>| -> Vec<Vec<String>> {
+        batches.into_iter().map(|batch| batch.into_iter().map(|item| item.code).collect()).collect()
+    };
+    let items = |codes: &[&str]| -> Vec<ItemParams> {
+        codes.iter().map(|code| ItemParams::builder(*code).build()).collect()
+    };
+
+    let writer = BulkWriter::new(&client).batch_size(2);
+    assert_eq!(codes(writer.batches(items(&["A", "B", "C", "D", "E"])).unwrap()), vec![vec!["A", "B"], vec!["C", "D"], vec!["E"]]);
+    assert!(writer.batches(Vec::<ItemParams>::new()).unwrap().is_empty());
+
+    // "<Items></Items>" and two of "<Item><Code>X</Code></Item>" are 16 + 2 * 28 bytes
+    let writer = BulkWriter::new(&client).max_body_size(72);
+    assert_eq!(codes(writer.batches(items(&["A", "B", "C"])).unwrap()), vec![vec!["A", "B"], vec!["C"]]);
+    let long = "L".repeat(100);
+    assert_eq!(codes(writer.batches(items(&["A", &long, "B"])).unwrap()), vec![vec!["A"], vec![long.as_str()], vec!["B"]]);
+}
+
+#[test]
+fn bulk_writer_offsets_indices_across_batches() {
+    let failed = r#"{"Code":"C","StatusAttributeString":"ERROR","HasValidationErrors":true,"ValidationErrors":[{"Message":"Item code 'C' already exists"}]}"#;
+    let script = Script::new(vec![
+        format!(r#"{{"Items":[{},{}]}}"#, item("A"), item("B")),
+        format!(r#"{{"Items":[{}]}}"#, failed),
+    ]);
+    let client = script.client();
+    let params = vec![ItemParams::builder("A").build(), ItemParams::builder("B").build(), ItemParams::builder("C").build()];
+
+    let results = BulkWriter::new(&client).batch_size(2).delay(std::time::Duration::from_millis(0)).put(params, Some("import")).unwrap();
+
+    assert_eq!(results[1].as_ref().map(|saved| saved.index).ok(), Some(1));
+    assert_eq!(results[2].as_ref().err().map(|error| error.index), Some(2));
+    let requests = script.requests();
+    assert_eq!(requests.len(), 2);
+    assert!(requests[1].headers.contains(&(String::from("Idempotency-Key"), String::from("import-1"))));
+}
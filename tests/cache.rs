@@ -0,0 +1,119 @@
+extern crate xero;
+
+mod common;
+
+use common::{respond, Script};
+use std::sync::Arc;
+use std::time::Duration;
+use xero::accounting::*;
+use xero::{Error, Request, ResponseCache};
+
+const WIDGET: &'static str = r#"{"Items":[{"ItemID":"7b3c5a6e-8e0f-4a6b-9d6e-0a1b2c3d4e5f","Code":"WIDGET","IsSold":true,"IsPurchased":true,"IsTrackedAsInventory":false}]}"#;
+const GADGET: &'static str = r#"{"Items":[{"ItemID":"9a8b7c6d-5e4f-4a3b-8c2d-1e0f9a8b7c6d","Code":"GADGET","IsSold":true,"IsPurchased":true,"IsTrackedAsInventory":false}]}"#;
+const NO_ITEMS: &'static str = r#"{"Items":[]}"#;
+
+fn has_header(request: &Request, name: &str) -> bool {
+    request.headers.iter().any(|&(ref key, _)| key == name)
+}
+
+#[test]
+fn cache_answers_fresh_requests() {
+    let script = Script::new(vec![WIDGET]);
+    let cache = Arc::new(ResponseCache::new(Duration::from_secs(300)));
+    let client = script.client().with_cache(cache.clone(), "org-1");
+
+    assert_eq!(Items::get(&client).unwrap().items[0].code, "WIDGET");
+    assert_eq!(Items::get(&client).unwrap().items[0].code, "WIDGET");
+    assert_eq!(script.requests().len(), 1);
+}
+
+#[test]
+fn cache_revalidates_expired_responses() {
+    let script = Script::new(vec![WIDGET, NO_ITEMS, GADGET, GADGET]);
+    let cache = Arc::new(ResponseCache::new(Duration::from_secs(0)));
+    let client = script.client().with_cache(cache, "org-1");
+
+    assert_eq!(Items::get(&client).unwrap().items[0].code, "WIDGET");
+    // Nothing modified since, so the cached list is still current
+    assert_eq!(Items::get(&client).unwrap().items[0].code, "WIDGET");
+    // Something was modified, so the whole list is fetched again
+    assert_eq!(Items::get(&client).unwrap().items[0].code, "GADGET");
+
+    let requests = script.requests();
+    assert_eq!(requests.len(), 4);
+    assert!(!has_header(&requests[0], "If-Modified-Since"));
+    assert!(has_header(&requests[1], "If-Modified-Since"));
+    assert!(has_header(&requests[2], "If-Modified-Since"));
+    assert!(!has_header(&requests[3], "If-Modified-Since"));
+}
+
+#[test]
+fn cache_keeps_changed_single_resources() {
+    let script = Script::with_results(vec![respond(200, WIDGET), respond(200, GADGET), respond(304, "")]);
+    let cache = Arc::new(ResponseCache::new(Duration::from_secs(0)));
+    let client = script.client().with_cache(cache, "org-1");
+    let id: ItemId = "7b3c5a6e-8e0f-4a6b-9d6e-0a1b2c3d4e5f".parse().unwrap();
+
+    assert_eq!(Item::get(&client, id).unwrap().code, "WIDGET");
+    // A changed resource is returned whole, so it isn't fetched a second time
+    assert_eq!(Item::get(&client, id).unwrap().code, "GADGET");
+    assert_eq!(Item::get(&client, id).unwrap().code, "GADGET");
+
+    let requests = script.requests();
+    assert_eq!(requests.len(), 3);
+    assert!(has_header(&requests[1], "If-Modified-Since"));
+    assert!(has_header(&requests[2], "If-Modified-Since"));
+}
+
+#[test]
+fn not_modified_is_only_accepted_for_conditional_requests() {
+    let client = Script::with_results(vec![respond(304, "")]).client();
+    match Items::get(&client) {
+        Err(Error::Xero(_, meta)) => assert_eq!(meta.status, 304),
+        other => panic!("expected a 304 to be an error, got {:?}", other.map(|items| items.items.len())),
+    }
+
+    let client = Script::with_results(vec![respond(304, "")]).client();
+    match Item::delete(&client, "7b3c5a6e-8e0f-4a6b-9d6e-0a1b2c3d4e5f".parse().unwrap(), None) {
+        Err(Error::Xero(_, meta)) => assert_eq!(meta.status, 304),
+        other => panic!("expected a 304 to be an error, got {:?}", other),
+    }
+}
+
+#[test]
+fn uncached_gets_bypass_and_update_the_cache() {
+    let script = Script::new(vec![WIDGET, GADGET]);
+    let cache = Arc::new(ResponseCache::new(Duration::from_secs(300)));
+    let client = script.client().with_cache(cache, "org-1");
+
+    assert_eq!(Items::get(&client).unwrap().items[0].code, "WIDGET");
+    let items: Items = client.get_uncached("/Items").unwrap();
+    assert_eq!(items.items[0].code, "GADGET");
+    assert_eq!(Items::get(&client).unwrap().items[0].code, "GADGET");
+    assert_eq!(script.requests().len(), 2);
+}
+
+#[test]
+fn cache_is_invalidated_by_writes() {
+    let script = Script::new(vec![WIDGET, GADGET, GADGET, WIDGET]);
+    let cache = Arc::new(ResponseCache::new(Duration::from_secs(300)));
+    let client = script.client().with_cache(cache.clone(), "org-1");
+    let other = script.client().with_cache(cache.clone(), "org-2");
+
+    Items::get(&client).unwrap();
+    Item::put(&client, ItemParams::builder("GADGET").build(), None).unwrap();
+    assert_eq!(Items::get(&client).unwrap().items[0].code, "GADGET");
+    assert_eq!(Items::get(&other).unwrap().items[0].code, "WIDGET");
+    assert_eq!(script.requests().len(), 4);
+}
+
+#[test]
+fn writes_affect_related_collections() {
+    assert_eq!(ResponseCache::affected_collections("Items"), vec!["Items"]);
+    assert_eq!(ResponseCache::affected_collections("Invoices"), vec!["Invoices", "Contacts"]);
+    assert_eq!(ResponseCache::affected_collections("ContactGroups"), vec!["ContactGroups", "Contacts"]);
+    let payments = ResponseCache::affected_collections("Payments");
+    for collection in &["Payments", "Invoices", "CreditNotes", "Prepayments", "Overpayments", "Contacts"] {
+        assert!(payments.contains(collection), "a payment should invalidate {}", collection);
+    }
+}
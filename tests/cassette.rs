@@ -0,0 +1,165 @@
+extern crate uuid;
+extern crate xero;
+
+mod common;
+
+use common::{Script, TestApplication};
+use std::env;
+use std::io::Read;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use xero::accounting::*;
+use xero::cassette::{Recorder, Replayer};
+use uuid::Uuid;
+use xero::{Client, Error, RateLimiter, Request, RequestError, Response, Transport};
+
+fn replay(cassette: &str) -> Client {
+    let path = format!("{}/tests/cassettes/{}", env!("CARGO_MANIFEST_DIR"), cassette);
+    Client::with_transport(TestApplication, Replayer::open(path).unwrap())
+}
+
+#[test]
+fn replay_get_contacts() {
+    let client = replay("contacts.json");
+    let contacts = Contacts::get(&client).unwrap();
+
+    assert_eq!(contacts.contacts.len(), 1);
+    assert_eq!(contacts.contacts[0].name, "ABC Limited");
+    assert_eq!(contacts.contacts[0].contact_id.to_string(), "bd2270c3-8706-4c11-9cfb-000b551c3f51");
+}
+
+#[test]
+fn replay_put_each_contacts() {
+    let client = replay("contacts.json");
+    let params = vec![
+        ContactParams::builder("Bayside Club").build(),
+        ContactParams::builder("ABC Limited").build(),
+    ];
+    let results = Contacts::put_each(&client, params, None).unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().map(|c| c.resource.name.as_str()).ok(), Some("Bayside Club"));
+    let error = results[1].as_ref().err().unwrap();
+    assert_eq!(error.index, 1);
+    assert!(error.validation_errors[0].message.starts_with("The contact name ABC Limited is already assigned"));
+}
+
+#[test]
+fn replay_from_shared_client() {
+    let client = Arc::new(replay("contacts.json").with_rate_limiter(Arc::new(RateLimiter::default())));
+
+    let reader = {
+        let client = client.clone();
+        thread::spawn(move || Contacts::get(&client).map(|contacts| contacts.contacts.len()))
+    };
+    let writer = {
+        let client = client.clone();
+        thread::spawn(move || {
+            let params = vec![
+                ContactParams::builder("Bayside Club").build(),
+                ContactParams::builder("ABC Limited").build(),
+            ];
+            Contacts::put_each(&client, params, None).map(|results| results.len())
+        })
+    };
+
+    assert_eq!(reader.join().unwrap().unwrap(), 1);
+    assert_eq!(writer.join().unwrap().unwrap(), 2);
+}
+
+#[test]
+fn record_then_replay() {
+    let path = env::temp_dir().join(format!("xero-rs-record-then-replay-{}-{}.json", process::id(), Uuid::new_v4()));
+    let script = Script::with_results(vec![Ok(Response{
+        status: 200,
+        headers: vec![(String::from("Xero-Correlation-Id"), String::from("c0ffee"))],
+        body: br#"{"Contacts":[]}"#.to_vec(),
+    })]);
+
+    let client = Client::with_transport(TestApplication, Recorder::new(script, &path));
+    assert_eq!(Contacts::get(&client).unwrap().contacts.len(), 0);
+
+    let replayer = Replayer::open(&path).unwrap();
+    let request = Request{
+        method: String::from("GET"),
+        url: String::from("https://api.xero.com/api.xro/2.0/Contacts"),
+        headers: vec![],
+        body: vec![],
+    };
+    assert_eq!(replayer.send(&request).unwrap().body, br#"{"Contacts":[]}"#.to_vec());
+    assert!(replayer.is_finished());
+    assert!(replayer.send(&request).is_err());
+
+    let mut recorded = String::new();
+    std::io::Read::read_to_string(&mut std::fs::File::open(&path).unwrap(), &mut recorded).unwrap();
+    assert!(!recorded.contains("oauth_signature"));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn replay_contact_group_membership() {
+    let client = replay("contact_groups.json");
+    let id: ContactGroupId = "97bbd0e6-ab4d-4117-9304-d90dd4779199".parse().unwrap();
+    let bayside: ContactId = "565acaa9-e7f3-4fbf-80c3-16b081ddae10".parse().unwrap();
+    let abc: ContactId = "bd2270c3-8706-4c11-9cfb-000b551c3f51".parse().unwrap();
+
+    let group = ContactGroup::get(&client, id).unwrap();
+    assert_eq!((group.name.as_str(), group.status), ("Preferred Suppliers", ContactGroupStatus::Active));
+    assert_eq!(group.contacts.iter().map(|c| c.contact_id).collect::<Vec<_>>(), vec![bayside]);
+
+    let added = ContactGroup::add_contacts(&client, id, vec![abc], None).unwrap();
+    assert_eq!((added[0].contact_id, added[0].name.as_str()), (abc, "ABC Limited"));
+    ContactGroup::remove_contact(&client, id, bayside, None).unwrap();
+}
+
+#[test]
+fn replay_invoice_pdf() {
+    let client = replay("invoice_pdf.json");
+    let id: InvoiceId = "243216c5-369e-4056-ac67-05388f86dc81".parse().unwrap();
+
+    let pdf = Invoice::pdf(&client, id).unwrap();
+    assert!(pdf.starts_with(b"%PDF-1.4\n%\xe2\xe3\xcf\xd3"));
+    assert!(pdf.ends_with(b"%%EOF\n"));
+
+    let mut streamed = Vec::new();
+    Invoice::pdf_reader(&client, id).unwrap().read_to_end(&mut streamed).unwrap();
+    assert_eq!(streamed, pdf);
+}
+
+#[test]
+fn replay_invoice_email_and_online_url() {
+    let client = replay("invoice_actions.json");
+    let authorised: InvoiceId = "243216c5-369e-4056-ac67-05388f86dc81".parse().unwrap();
+    let draft: InvoiceId = "8f6c1a3e-2b4d-4e5f-9a7b-6c5d4e3f2a10".parse().unwrap();
+    let over_limit: InvoiceId = "4d3c2b1a-0f9e-4d8c-b7a6-958473625140".parse().unwrap();
+    let bill: InvoiceId = "1a2b3c4d-5e6f-4a7b-8c9d-0e1f2a3b4c5d".parse().unwrap();
+
+    assert_eq!(Invoice::online_url(&client, authorised).unwrap(), "https://in.xero.com/iztKMjyAEJT7MVnmruxgCdIJUDStfRgmtdQSIW13");
+    Invoice::email(&client, authorised, None).unwrap();
+
+    match Invoice::email(&client, draft, None) {
+        Err(Error::Invoice(InvoiceError::InvalidStatus, Some(meta))) => assert_eq!(meta.status, 400),
+        other => panic!("expected an invalid status, got {:?}", other),
+    }
+    match Invoice::online_url(&client, draft) {
+        Err(Error::Invoice(InvoiceError::InvalidStatus, _)) => {}
+        other => panic!("expected an invalid status, got {:?}", other),
+    }
+    match Invoice::email(&client, over_limit, None) {
+        Err(Error::Invoice(InvoiceError::EmailLimitReached, _)) => {}
+        other => panic!("expected the email limit, got {:?}", other),
+    }
+    match Invoice::email(&client, bill, None) {
+        Err(Error::Invoice(InvoiceError::NotSalesInvoice, _)) => {}
+        other => panic!("expected a sales invoice error, got {:?}", other),
+    }
+    // Unrecognised messages, even ones mentioning a type, are left as Xero reported them
+    match Invoice::online_url(&client, bill) {
+        Err(Error::Xero(RequestError::Validation(error), meta)) => {
+            assert_eq!(meta.status, 400);
+            assert!(error.elements[0].validation_errors[0].message.starts_with("The TaxType code 'OUTPUT9'"));
+        }
+        other => panic!("expected a Xero validation error, got {:?}", other),
+    }
+}
@@ -0,0 +1,58 @@
+//! Fixtures shared by the integration tests.
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+use xero::{Application, Client, Error, Request, Response, Transport};
+
+/// Signs every request with the same fake signature, which should never be logged or recorded.
+pub struct TestApplication;
+
+impl Application for TestApplication {
+    fn get_signature(&self, _: &str, _: &str) -> Result<String, Error> {
+        Ok(String::from("OAuth oauth_signature=\"secret\""))
+    }
+}
+
+/// Answers requests with scripted results in order, remembering each request.
+///
+/// A request after the script has run out fails the test, so `Script::default()` checks
+/// nothing is sent at all.
+#[derive(Clone, Default)]
+pub struct Script {
+    results: Arc<Mutex<Vec<Result<Response, Error>>>>,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl Script {
+    /// Answers with each of `bodies` in turn, with a 200 status.
+    pub fn new<B: Into<Vec<u8>>>(bodies: Vec<B>) -> Script {
+        Script::with_results(bodies.into_iter().map(|body| respond(200, body)).collect())
+    }
+
+    pub fn with_results(results: Vec<Result<Response, Error>>) -> Script {
+        Script{results: Arc::new(Mutex::new(results)), requests: Arc::default()}
+    }
+
+    /// The requests sent so far.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn client(&self) -> Client {
+        Client::with_transport(TestApplication, self.clone())
+    }
+}
+
+impl Transport for Script {
+    fn send(&self, request: &Request) -> Result<Response, Error> {
+        self.requests.lock().unwrap().push(request.clone());
+        let mut results = self.results.lock().unwrap();
+        assert!(!results.is_empty(), "unexpected {} request to {}", request.method, request.url);
+        results.remove(0)
+    }
+}
+
+/// A response with `status` and `body`, and no headers.
+pub fn respond<B: Into<Vec<u8>>>(status: u16, body: B) -> Result<Response, Error> {
+    Ok(Response{status: status, headers: Vec::new(), body: body.into()})
+}
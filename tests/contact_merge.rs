@@ -0,0 +1,137 @@
+#[macro_use]
+extern crate serde_json;
+extern crate xero;
+
+mod common;
+
+use common::Script;
+use serde_json as json;
+use std::sync::Arc;
+use std::time::Duration;
+use xero::accounting::*;
+use xero::ResponseCache;
+
+fn contact(n: u32, status: &str, name: &str, email: &str, tax_number: &str) -> json::Value {
+    json!({
+        "ContactID": format!("00000000-0000-4000-8000-{:012}", n),
+        "ContactStatus": status,
+        "Name": name,
+        "EmailAddress": email,
+        "TaxNumber": tax_number,
+        "Addresses": [],
+        "Phones": [],
+        "IsSupplier": false,
+        "IsCustomer": true,
+    })
+}
+
+fn contacts(contacts: Vec<json::Value>) -> Contacts {
+    json::from_value(json!({"Contacts": contacts})).unwrap()
+}
+
+fn id(n: u32) -> ContactId {
+    format!("00000000-0000-4000-8000-{:012}", n).parse().unwrap()
+}
+
+#[test]
+fn duplicates_are_matched_on_normalised_keys() {
+    let plans = contacts(vec![
+        contact(1, "ACTIVE", "ABC Limited", "accounts@abc.example", ""),
+        contact(2, "ACTIVE", "ABC Ltd", " Accounts@ABC.example ", ""),
+        contact(3, "ACTIVE", "Bayside Club", "", "12-345-678"),
+        contact(4, "ACTIVE", "A.B.C. Limited", "", ""),
+        contact(5, "ACTIVE", "Bayside Social Club", "club@bayside.example", "12 345 678"),
+        contact(6, "ARCHIVED", "ABC Limited", "", ""),
+        contact(7, "ACTIVE", "Marine Systems", "", ""),
+    ]).find_duplicates();
+
+    assert_eq!(plans.len(), 2);
+    assert_eq!((plans[0].keep, plans[0].name.as_str()), (id(1), "ABC Limited"));
+    assert_eq!(plans[0].duplicates.iter().map(|d| (d.contact_id, d.reason)).collect::<Vec<_>>(), vec![
+        (id(2), DuplicateReason::EmailAddress),
+        (id(4), DuplicateReason::Name),
+    ]);
+    assert_eq!(plans[1].keep, id(3));
+    assert_eq!(plans[1].duplicates.iter().map(|d| (d.contact_id, d.reason)).collect::<Vec<_>>(), vec![
+        (id(5), DuplicateReason::TaxNumber),
+    ]);
+}
+
+#[test]
+fn duplicates_are_joined_through_shared_contacts() {
+    // 3 shares an email address with 1 and a name with 2, so all three are one set
+    let plans = contacts(vec![
+        contact(1, "ACTIVE", "Ridgeway University", "ap@ridgeway.example", ""),
+        contact(2, "ACTIVE", "Ridgeway Uni", "", ""),
+        contact(3, "ACTIVE", "Ridgeway Uni.", "AP@ridgeway.example", ""),
+    ]).find_duplicates();
+
+    assert_eq!(plans.len(), 1);
+    assert_eq!(plans[0].keep, id(1));
+    assert_eq!(plans[0].duplicates.iter().map(|d| (d.contact_id, d.matched_with, d.reason)).collect::<Vec<_>>(), vec![
+        (id(2), id(3), DuplicateReason::Name),
+        (id(3), id(1), DuplicateReason::EmailAddress),
+    ]);
+}
+
+#[test]
+fn duplicates_keep_the_reason_they_were_joined_by() {
+    // 3 only matches 2, which only joins 1's set because 3 shares an email address with it
+    let plans = contacts(vec![
+        contact(1, "ACTIVE", "Harbour Freight", "accounts@harbour.example", ""),
+        contact(2, "ACTIVE", "Harbour Logistics", "", "12-345-678"),
+        contact(3, "ACTIVE", "HF Shipping", "ACCOUNTS@harbour.example", "12 345 678"),
+    ]).find_duplicates();
+
+    assert_eq!(plans.len(), 1);
+    assert_eq!(plans[0].keep, id(1));
+    assert_eq!(plans[0].duplicates.iter().map(|d| (d.contact_id, d.matched_with, d.reason)).collect::<Vec<_>>(), vec![
+        (id(2), id(3), DuplicateReason::TaxNumber),
+        (id(3), id(1), DuplicateReason::EmailAddress),
+    ]);
+
+    // The same chain the other way round: A~B by email, then B~C by tax number
+    let plans = contacts(vec![
+        contact(1, "ACTIVE", "Harbour Freight", "accounts@harbour.example", ""),
+        contact(2, "ACTIVE", "HF Shipping", "accounts@harbour.example", "12-345-678"),
+        contact(3, "ACTIVE", "Harbour Logistics", "", "12 345 678"),
+    ]).find_duplicates();
+
+    assert_eq!(plans[0].duplicates.iter().map(|d| (d.contact_id, d.matched_with, d.reason)).collect::<Vec<_>>(), vec![
+        (id(2), id(1), DuplicateReason::EmailAddress),
+        (id(3), id(2), DuplicateReason::TaxNumber),
+    ]);
+}
+
+#[test]
+fn merging_reads_open_invoices_uncached() {
+    let no_invoices = json!({"Invoices": []}).to_string();
+    let invoices = json!({"Invoices": [{
+        "Type": "ACCREC",
+        "InvoiceID": "243216c5-369e-4056-ac67-05388f86dc81",
+        "InvoiceNumber": "INV-0001",
+        "Contact": {"ContactID": format!("00000000-0000-4000-8000-{:012}", 2), "Name": "ABC Ltd"},
+        "DateString": "2017-10-20T00:00:00",
+        "Status": "AUTHORISED",
+        "LineAmountTypes": "Exclusive",
+        "TotalTax": 15.00,
+        "Total": 115.00,
+    }]}).to_string();
+    let archived = json!({"Contacts": [contact(2, "ARCHIVED", "ABC Ltd", "", "")]}).to_string();
+    let script = Script::new(vec![no_invoices, invoices.clone(), invoices, archived]);
+    let cache = Arc::new(ResponseCache::new(Duration::from_secs(300)));
+    let client = script.client().with_cache(cache, "org-1");
+
+    // The cached list is out of date by the time the plan is executed
+    assert!(Invoices::get_for_contact(&client, id(2), OPEN_INVOICE_STATUSES).unwrap().invoices.is_empty());
+    let plan = MergePlan{
+        keep: id(1),
+        name: String::from("ABC Limited"),
+        duplicates: vec![Duplicate{contact_id: id(2), name: String::from("ABC Ltd"), matched_with: id(1), reason: DuplicateReason::EmailAddress}],
+    };
+    let moved = plan.execute(&client).unwrap();
+
+    assert_eq!(moved.len(), 1);
+    let methods: Vec<String> = script.requests().into_iter().map(|request| request.method).collect();
+    assert_eq!(methods, vec!["GET", "GET", "POST", "POST"]);
+}
@@ -6,6 +6,7 @@ extern crate xero;
 use bigdecimal::BigDecimal;
 use chrono::NaiveDate;
 use serde_json as json;
+use std::str::FromStr;
 use xero::accounting::*;
 use xero::encoding::XmlSerializable;
 
@@ -21,6 +22,19 @@ fn _json(example: &'static str) -> Option<String> {
     Some(String::from(example))
 }
 
+#[test]
+fn serialize_resource_ids() {
+    let id: ContactId = "243216C5-369E-4056-AC67-05388F86DC81".parse().unwrap();
+    let params = ContactIdParams::from(id);
+
+    assert_eq!(id.to_string(), "243216c5-369e-4056-ac67-05388f86dc81");
+    assert_eq!(params.to_xml().ok(), _xml("<ContactID>243216c5-369e-4056-ac67-05388f86dc81</ContactID>"));
+    assert_eq!(json::to_string(&params).ok(), _json(r#"{"ContactID":"243216c5-369e-4056-ac67-05388f86dc81"}"#));
+    assert_eq!(json::from_str::<ContactId>(r#""243216c5-369e-4056-ac67-05388f86dc81""#).ok(), Some(id));
+    assert!("INV-0001".parse::<InvoiceId>().is_err());
+    assert!(json::from_str::<ContactId>(r#""INV-0001""#).is_err());
+}
+
 #[test]
 fn serialize_item_details() {
     let item = ItemDetails{
@@ -62,7 +76,7 @@ fn serialized_payment_params() {
 #[test]
 fn dserialized_payment() {
     let data = r#"{
-        "PaymentID": "payment-id",
+        "PaymentID": "0d666415-cf77-43fa-80c7-56775591d426",
         "Amount": 0.0000,
         "PaymentType": "ACCRECPAYMENT",
         "Status": "AUTHORISED",
@@ -72,8 +86,9 @@ fn dserialized_payment() {
     let payment: Payment = json::from_str(&data).unwrap();
 
     assert_eq!(payment, Payment{
-        payment_id: String::from("payment-id"),
+        payment_id: "0d666415-cf77-43fa-80c7-56775591d426".parse().unwrap(),
         amount: BigDecimal::from(0).with_scale(4),
+        currency_rate: None,
         payment_type: PaymentType::AccountsReceivable,
         status: PaymentStatus::Authorised,
         is_reconciled: true,
@@ -83,47 +98,50 @@ fn dserialized_payment() {
 
 #[test]
 fn serialize_invoice_params() {
-    let mut invoice = InvoiceParams::default();
-    invoice.contact = ContactIdParams{contact_id: "eaa28f49-6028-4b6e-bb12-d8f6278073fc"};
+    let contact_id: ContactId = "eaa28f49-6028-4b6e-bb12-d8f6278073fc".parse().unwrap();
+    let line_item = LineItemParams::builder("Consulting services as agreed").build();
+    let invoice = InvoiceParams::builder(InvoiceType::AccountsReceivable, contact_id, line_item).build();
 
     assert_eq!(invoice.to_xml().ok(), _xml("
 <Type>ACCREC</Type>
 <Contact>
   <ContactID>eaa28f49-6028-4b6e-bb12-d8f6278073fc</ContactID>
 </Contact>
-<LineItems />"));
+<LineItems>
+  <LineItem>
+    <Description>Consulting services as agreed</Description>
+  </LineItem>
+</LineItems>"));
     assert_eq!(json::to_string_pretty(&invoice).ok(), _json(r#"{
   "Type": "ACCREC",
   "Contact": {
     "ContactID": "eaa28f49-6028-4b6e-bb12-d8f6278073fc"
   },
-  "LineItems": []
+  "LineItems": [
+    {
+      "Description": "Consulting services as agreed",
+      "Quantity": null,
+      "UnitAmount": null
+    }
+  ]
 }"#));
 
-    let invoice = InvoiceParams{
-        invoice_type: InvoiceType::AccountsReceivable,
-        contact: ContactIdParams{contact_id: "eaa28f49-6028-4b6e-bb12-d8f6278073fc"},
-        date: Some(NaiveDate::from_ymd(2009, 08, 30)),
-        due_date: Some(NaiveDate::from_ymd(2009, 09, 20)),
-        invoice_number: Some("0010"),
-        reference: Some("Ref:ABC"),
-        url: Some("https://twitter.com/SuperTransparentInvoices/status/865425833631993856"),
-        status: Some(InvoiceStatus::Authorised),
-        sent_to_contact: Some(true),
-        line_amount_types: Some(LineAmountType::Exclusive),
-        line_items: vec![
-            LineItemParams{
-                item_code: None,
-                description: "Consulting services as agreed",
-                quantity: Some(5.0),
-                unit_amount: Some(BigDecimal::from(0).with_scale(4)),
-                line_amount: Some(BigDecimal::from(0).with_scale(4)),
-                tax_amount: None,
-                account_code: Some("200"),
-                discount_rate: None,
-            }
-        ],
-    };
+    let line_item = LineItemParams::builder("Consulting services as agreed")
+        .quantity(5.0)
+        .unit_amount(BigDecimal::from(0).with_scale(4))
+        .line_amount(BigDecimal::from(0).with_scale(4))
+        .account_code("200")
+        .build();
+    let invoice = InvoiceParams::builder(InvoiceType::AccountsReceivable, contact_id, line_item)
+        .date(NaiveDate::from_ymd(2009, 08, 30))
+        .due_date(NaiveDate::from_ymd(2009, 09, 20))
+        .invoice_number("0010")
+        .reference("Ref:ABC")
+        .url("https://twitter.com/SuperTransparentInvoices/status/865425833631993856")
+        .status(InvoiceStatus::Authorised)
+        .sent_to_contact(true)
+        .line_amount_types(LineAmountType::Exclusive)
+        .build();
 
     assert_eq!(invoice.to_xml().ok(), _xml("
 <Type>ACCREC</Type>
@@ -148,3 +166,198 @@ fn serialize_invoice_params() {
   </LineItem>
 </LineItems>"));
 }
+
+#[test]
+fn serialize_contact_params() {
+    let contact = ContactParams::builder("ABC Limited")
+        .contact_person(ContactPerson{
+            first_name: String::from("John"),
+            last_name: String::from("Smith"),
+            email_address: String::new(),
+            include_in_emails: true,
+        })
+        .default_currency("NZD")
+        .sales_tracking_category(ContactTrackingCategory{
+            tracking_category_name: String::from("Region"),
+            tracking_option_name: String::from("North"),
+        })
+        .bills_payment_term(PaymentTerm{day: 15, term_type: PaymentTermType::OfFollowingMonth})
+        .build();
+
+    assert_eq!(contact.to_xml().ok(), _xml("
+<Name>ABC Limited</Name>
+<ContactPersons>
+  <ContactPerson>
+    <FirstName>John</FirstName>
+    <LastName>Smith</LastName>
+    <IncludeInEmails>true</IncludeInEmails>
+  </ContactPerson>
+</ContactPersons>
+<DefaultCurrency>NZD</DefaultCurrency>
+<SalesTrackingCategories>
+  <SalesTrackingCategory>
+    <TrackingCategoryName>Region</TrackingCategoryName>
+    <TrackingOptionName>North</TrackingOptionName>
+  </SalesTrackingCategory>
+</SalesTrackingCategories>
+<PaymentTerms>
+  <Bills>
+    <Day>15</Day>
+    <Type>OFFOLLOWINGMONTH</Type>
+  </Bills>
+</PaymentTerms>"));
+}
+
+#[test]
+fn deserialize_contact() {
+    let data = r#"{
+        "ContactID": "bd2270c3-8706-4c11-9cfb-000b551c3f51",
+        "ContactStatus": "ACTIVE",
+        "Name": "ABC Limited",
+        "Addresses": [],
+        "Phones": [],
+        "IsSupplier": false,
+        "IsCustomer": true,
+        "DefaultCurrency": "NZD",
+        "UpdatedDateUTC": "/Date(1488391422280+0000)/",
+        "ContactGroups": [{
+            "ContactGroupID": "26fe5b3b-3f0d-4b4a-86a5-9d6b0a3a5c1e",
+            "Name": "VIP",
+            "Status": "ACTIVE"
+        }],
+        "PaymentTerms": {"Sales": {"Day": 20, "Type": "DAYSAFTERBILLDATE"}},
+        "Balances": {"AccountsReceivable": {"Outstanding": 760.00, "Overdue": 0.00}},
+        "HasAttachments": true
+    }"#;
+
+    let contact: Contact = json::from_str(data).unwrap();
+
+    assert_eq!(contact.default_currency, Some(String::from("NZD")));
+    assert_eq!(contact.updated_date_utc.map(|date| date.timestamp()), Some(1488391422));
+    assert_eq!(contact.contact_groups[0].name, "VIP");
+    assert_eq!(contact.payment_terms.and_then(|terms| terms.sales).map(|term| term.term_type), Some(PaymentTermType::DaysAfterBillDate));
+    assert_eq!(contact.balances.and_then(|b| b.accounts_receivable).map(|ar| ar.outstanding), Some(BigDecimal::from(760)));
+    assert!(contact.contact_persons.is_empty());
+    assert!(contact.has_attachments);
+}
+
+#[test]
+fn serialize_line_item_tracking() {
+    let line_item = LineItemParams::builder("Consulting")
+        .tax_type("OUTPUT2")
+        .tracking("Region", "North")
+        .build();
+
+    assert_eq!(line_item.to_xml().ok(), _xml("
+<Description>Consulting</Description>
+<TaxType>OUTPUT2</TaxType>
+<Tracking>
+  <TrackingCategory>
+    <Name>Region</Name>
+    <Option>North</Option>
+  </TrackingCategory>
+</Tracking>"));
+}
+
+#[test]
+fn deserialize_invoice() {
+    let data = r#"{
+        "Type": "ACCREC",
+        "InvoiceID": "243216c5-369e-4056-ac67-05388f86dc81",
+        "InvoiceNumber": "OIT00546",
+        "Contact": {"ContactID": "bd2270c3-8706-4c11-9cfb-000b551c3f51", "Name": "ABC Limited"},
+        "DateString": "2009-08-30T00:00:00",
+        "DueDateString": "2009-09-20T00:00:00",
+        "Status": "AUTHORISED",
+        "LineAmountTypes": "Exclusive",
+        "LineItems": [{
+            "LineItemID": "52208ff9-528a-4985-a9ad-b2b1d4210e38",
+            "Description": "Onsite project management",
+            "Quantity": 1.0000,
+            "UnitAmount": 1800.00,
+            "TaxType": "OUTPUT",
+            "TaxAmount": 225.00,
+            "LineAmount": 1800.00,
+            "AccountCode": "200",
+            "Tracking": [{"TrackingCategoryID": "e2f2f732-e92a-4f3a-9c4d-ee4da0182a13", "Name": "Region", "Option": "North"}]
+        }],
+        "SubTotal": 1800.00,
+        "TotalTax": 225.00,
+        "Total": 2025.00,
+        "AmountDue": 1025.00,
+        "AmountPaid": 1000.00,
+        "AmountCredited": 0.00,
+        "CurrencyCode": "NZD",
+        "Payments": [{
+            "PaymentID": "0d666415-cf77-43fa-80c7-56775591d426",
+            "Date": "/Date(1518134400000+0000)/",
+            "Amount": 1000.00
+        }],
+        "HasAttachments": false,
+        "UpdatedDateUTC": "/Date(1518685950940+0000)/"
+    }"#;
+
+    let invoice: Invoice = json::from_str(data).unwrap();
+
+    assert_eq!(invoice.due_date, Some(NaiveDate::from_ymd(2009, 9, 20).and_hms(0, 0, 0)));
+    assert_eq!(invoice.subtotal, Some(BigDecimal::from(1800)));
+    assert_eq!(invoice.line_items[0].tracking[0].option, "North");
+    assert_eq!(invoice.line_items[0].line_amount, Some(BigDecimal::from(1800)));
+    assert_eq!((invoice.amount_due, invoice.amount_paid), (BigDecimal::from(1025), BigDecimal::from(1000)));
+    assert_eq!(invoice.payments[0].amount, BigDecimal::from(1000));
+    assert_eq!(invoice.currency_code, Some(String::from("NZD")));
+    assert!(invoice.updated_date_utc.is_some());
+}
+
+#[test]
+fn serialize_contact_group_params() {
+    assert_eq!(ContactGroupParams::new("Preferred Suppliers").to_xml().ok(), _xml("<Name>Preferred Suppliers</Name>"));
+    let deleted = ContactGroupParams{name: None, status: Some(ContactGroupStatus::Deleted)};
+    assert_eq!(deleted.to_xml().ok(), _xml("<Status>DELETED</Status>"));
+}
+
+#[test]
+fn deserialize_invoice_allocations() {
+    let data = r#"{
+        "Type": "ACCREC",
+        "InvoiceID": "243216c5-369e-4056-ac67-05388f86dc81",
+        "InvoiceNumber": "INV-0042",
+        "Contact": {"ContactID": "bd2270c3-8706-4c11-9cfb-000b551c3f51", "Name": "ABC Limited"},
+        "DateString": "2017-10-20T00:00:00",
+        "Status": "AUTHORISED",
+        "LineAmountTypes": "Exclusive",
+        "LineItems": [{
+            "Description": "Membership",
+            "Quantity": 1.0000,
+            "UnitAmount": 80.00,
+            "TaxAmount": 9.19,
+            "LineAmount": 70.00,
+            "DiscountRate": 12.50
+        }],
+        "SubTotal": 70.00,
+        "TotalTax": 9.19,
+        "Total": 79.19,
+        "TotalDiscount": 10.00,
+        "AmountDue": 12.53,
+        "AmountPaid": 33.33,
+        "AmountCredited": 33.33,
+        "CreditNotes": [{
+            "CreditNoteID": "d8d2b1e4-7f7b-4b87-8c3c-3f0e3a4e5b61",
+            "CreditNoteNumber": "CN-0007",
+            "AppliedAmount": 33.33,
+            "Total": 45.10
+        }],
+        "Prepayments": [],
+        "Overpayments": []
+    }"#;
+
+    let invoice: Invoice = json::from_str(data).unwrap();
+    let decimal = |s: &str| BigDecimal::from_str(s).unwrap();
+
+    assert_eq!(invoice.line_items[0].discount_rate, Some(decimal("12.5")));
+    assert_eq!(invoice.line_items[0].tax_amount, Some(decimal("9.19")));
+    assert_eq!((invoice.total.clone(), invoice.total_discount.clone()), (decimal("79.19"), Some(decimal("10"))));
+    assert_eq!(invoice.amount_paid.clone() + invoice.amount_credited.clone() + invoice.amount_due.clone(), invoice.total);
+    assert_eq!(invoice.credit_notes[0].applied_amount, Some(decimal("33.33")));
+    assert_eq!(invoice.credit_notes[0].total, Some(decimal("45.1")));
+}
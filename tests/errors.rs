@@ -0,0 +1,77 @@
+extern crate serde_json;
+extern crate xero;
+
+use serde_json as json;
+use xero::{RequestError, ResponseMeta};
+
+#[test]
+fn deserialize_validation_error() {
+    let data = r#"{
+        "ErrorNumber": 10,
+        "Type": "ValidationException",
+        "Message": "A validation exception occurred",
+        "Elements": [
+            {
+                "InvoiceNumber": "INV-0001",
+                "HasValidationErrors": false
+            },
+            {
+                "InvoiceNumber": "INV-0002",
+                "HasValidationErrors": true,
+                "ValidationErrors": [
+                    {"Message": "Email address must be valid."},
+                    {"Message": "Account code '999' is not a valid code for this document."}
+                ],
+                "Warnings": [
+                    {"Message": "Only AUTHORISED and SUBMITTED invoices can be emailed."}
+                ]
+            }
+        ]
+    }"#;
+
+    let error = match json::from_str(data).unwrap() {
+        RequestError::Validation(error) => error,
+        other => panic!("expected a validation error, got {:?}", other),
+    };
+
+    assert_eq!(error.error_number, 10);
+    assert_eq!(error.error_type, "ValidationException");
+    assert_eq!(error.elements.len(), 2);
+
+    let failed = error.failed_elements();
+    assert_eq!(failed.len(), 1);
+    assert_eq!(failed[0].index, 1);
+    assert_eq!(failed[0].validation_errors[0].message, "Email address must be valid.");
+    assert_eq!(failed[0].warnings[0].message, "Only AUTHORISED and SUBMITTED invoices can be emailed.");
+
+    assert_eq!(error.to_string(), "ValidationException 10: A validation exception occurred \
+        [element 1: Email address must be valid.; Account code '999' is not a valid code for this document.]");
+}
+
+#[test]
+fn deserialize_status_error() {
+    let data = r#"{"Id": "9b1a5e7e", "Status": "NotFound", "ProviderName": "Example App"}"#;
+
+    match json::from_str(data).unwrap() {
+        RequestError::Status(error) => assert_eq!(error.to_string(), "NotFound (Example App)"),
+        other => panic!("expected a status error, got {:?}", other),
+    }
+}
+
+#[test]
+fn response_meta_from_headers() {
+    let meta = ResponseMeta::new(429, vec![
+        (String::from("Date"), String::from("Tue, 15 Nov 1994 08:12:31 GMT")),
+        (String::from("xero-correlation-id"), String::from("4f5d5d0c-a6c4-4c4d-8d3f-a0c7bb3b1e3c")),
+        (String::from("X-MinLimit-Remaining"), String::from("0")),
+        (String::from("Retry-After"), String::from("37")),
+        (String::from("X-Rate-Limit-Problem"), String::from("minute")),
+    ]);
+
+    assert_eq!(meta.correlation_id, Some(String::from("4f5d5d0c-a6c4-4c4d-8d3f-a0c7bb3b1e3c")));
+    assert_eq!(meta.date.map(|date| date.timestamp()), Some(784887151));
+    assert_eq!(meta.rate_limit.minute_remaining, Some(0));
+    assert_eq!(meta.rate_limit.day_remaining, None);
+    assert_eq!(meta.rate_limit.retry_after, Some(37));
+    assert_eq!(meta.rate_limit.problem, Some(String::from("minute")));
+}
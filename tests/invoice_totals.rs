@@ -0,0 +1,127 @@
+extern crate bigdecimal;
+#[macro_use]
+extern crate serde_json;
+extern crate xero;
+
+use bigdecimal::BigDecimal;
+use std::str::FromStr;
+use xero::accounting::*;
+
+fn decimal(s: &str) -> BigDecimal {
+    BigDecimal::from_str(s).unwrap()
+}
+
+fn calculator() -> InvoiceCalculator {
+    InvoiceCalculator::new()
+        .tax_rate("OUTPUT2", decimal("15"))
+        .tax_rate("NONE", decimal("0"))
+}
+
+fn invoice(line_amount_type: LineAmountType, line_items: Vec<LineItemParams>) -> InvoiceParams {
+    let mut line_items = line_items.into_iter();
+    let contact_id = ContactId::from_str("565acaa9-e7f3-4fbf-80c3-16b081ddae10").unwrap();
+    let mut builder = InvoiceParams::builder(InvoiceType::AccountsReceivable, contact_id, line_items.next().unwrap())
+        .line_amount_types(line_amount_type);
+    for line in line_items {
+        builder = builder.line_item(line);
+    }
+    builder.build()
+}
+
+#[test]
+fn calculate_exclusive_totals() {
+    let params = invoice(LineAmountType::Exclusive, vec![
+        LineItemParams::builder("Consulting").quantity(3.0).unit_amount(decimal("33.335")).tax_type("OUTPUT2").build(),
+        LineItemParams::builder("Support").quantity(1.0).unit_amount(decimal("99.99")).discount_rate(decimal("10")).tax_type("OUTPUT2").build(),
+        LineItemParams::builder("Postage").unit_amount(decimal("4.50")).tax_type("NONE").build(),
+    ]);
+    let totals = calculator().calculate(&params).unwrap();
+
+    // 33.335 is rounded to 33.34 before multiplying, and 10% off 99.99 is 89.991
+    assert_eq!(totals.line_items[0].line_amount, decimal("100.02"));
+    assert_eq!(totals.line_items[1].line_amount, decimal("89.99"));
+    assert_eq!(totals.line_items[1].discount, decimal("10.00"));
+    assert_eq!(totals.line_items[0].tax_amount, decimal("15.00"));
+    assert_eq!(totals.line_items[1].tax_amount, decimal("13.50"));
+    assert_eq!(totals.sub_total, decimal("194.51"));
+    assert_eq!(totals.total_tax, decimal("28.50"));
+    assert_eq!(totals.total, decimal("223.01"));
+    assert_eq!(totals.total_discount, decimal("10.00"));
+}
+
+#[test]
+fn calculate_inclusive_and_untaxed_totals() {
+    let lines = || vec![
+        LineItemParams::builder("Widget").quantity(2.0).unit_amount(decimal("57.50")).tax_type("OUTPUT2").build(),
+        LineItemParams::builder("Gadget").quantity(0.5).unit_amount(decimal("10.01")).tax_type("OUTPUT2").build(),
+    ];
+    let totals = calculator().calculate(&invoice(LineAmountType::Inclusive, lines())).unwrap();
+    assert_eq!(totals.line_items[0].tax_amount, decimal("15.00"));
+    assert_eq!(totals.line_items[1].line_amount, decimal("5.01"));
+    assert_eq!(totals.line_items[1].tax_amount, decimal("0.65"));
+    assert_eq!(totals.total, decimal("120.01"));
+    assert_eq!(totals.sub_total, decimal("104.36"));
+
+    let totals = calculator().calculate(&invoice(LineAmountType::NoTax, lines())).unwrap();
+    assert_eq!(totals.total_tax, decimal("0"));
+    assert_eq!(totals.sub_total, totals.total);
+}
+
+#[test]
+fn calculate_four_decimal_unit_amounts() {
+    let params = invoice(LineAmountType::Exclusive, vec![
+        LineItemParams::builder("Bolts").quantity(1000.0).unit_amount(decimal("0.01235")).tax_type("NONE").build(),
+    ]);
+    assert_eq!(calculator().calculate(&params).unwrap().total, decimal("10.00"));
+    assert_eq!(calculator().unit_decimal_places(4).calculate(&params).unwrap().total, decimal("12.40"));
+}
+
+#[test]
+fn calculate_requires_tax_rates() {
+    let params = invoice(LineAmountType::Exclusive, vec![
+        LineItemParams::builder("Consulting").unit_amount(decimal("10")).tax_type("INPUT2").build(),
+        LineItemParams::builder("Support").unit_amount(decimal("10")).build(),
+    ]);
+    assert_eq!(calculator().calculate(&params), Err(TotalsError::UnknownTaxType{line: 0, tax_type: String::from("INPUT2")}));
+    let params = invoice(LineAmountType::Exclusive, params.line_items[1..].to_vec());
+    assert_eq!(calculator().calculate(&params), Err(TotalsError::MissingTaxType{line: 0}));
+}
+
+#[test]
+fn calculate_fractional_discount_rates() {
+    let params = invoice(LineAmountType::Exclusive, vec![
+        LineItemParams::builder("Membership").unit_amount(decimal("80.00")).discount_rate(decimal("12.5")).tax_type("OUTPUT2").build(),
+    ]);
+    let totals = calculator().calculate(&params).unwrap();
+    assert_eq!(totals.line_items[0].line_amount, decimal("70.00"));
+    assert_eq!(totals.line_items[0].discount, decimal("10.00"));
+    assert_eq!(totals.total, decimal("80.50"));
+}
+
+#[test]
+fn compare_totals_with_xero() {
+    let params = invoice(LineAmountType::Exclusive, vec![
+        LineItemParams::builder("Consulting").quantity(3.0).unit_amount(decimal("33.335")).tax_type("OUTPUT2").build(),
+    ]);
+    let totals = calculator().calculate(&params).unwrap();
+    let returned = |tax: f64, total: f64| -> Invoice {
+        serde_json::from_value(json!({
+            "Type": "ACCREC",
+            "InvoiceID": "243216c5-369e-4056-ac67-05388f86dc81",
+            "InvoiceNumber": "INV-0001",
+            "Contact": {"ContactID": "565acaa9-e7f3-4fbf-80c3-16b081ddae10", "Name": "Bayside Club"},
+            "DateString": "2017-10-20T00:00:00",
+            "Status": "DRAFT",
+            "LineAmountTypes": "Exclusive",
+            "LineItems": [{"Description": "Consulting", "Quantity": 3.0, "UnitAmount": 33.34, "TaxAmount": tax, "LineAmount": 100.02}],
+            "SubTotal": 100.02,
+            "TotalTax": tax,
+            "Total": total,
+        })).unwrap()
+    };
+
+    assert!(totals.matches(&returned(15.00, 115.02)));
+    let mismatches = totals.mismatches(&returned(15.01, 115.03));
+    assert_eq!(mismatches.iter().map(|m| m.field.as_str()).collect::<Vec<_>>(), vec!["TotalTax", "Total", "LineItems[0].TaxAmount"]);
+    assert_eq!((mismatches[1].expected.clone(), mismatches[1].actual.clone()), (decimal("115.02"), decimal("115.03")));
+}
@@ -0,0 +1,136 @@
+extern crate bigdecimal;
+#[macro_use]
+extern crate serde_json;
+extern crate xero;
+
+mod common;
+
+use bigdecimal::BigDecimal;
+use common::Script;
+use serde_json as json;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use xero::accounting::*;
+use xero::{Error, ResponseCache};
+
+const INVOICE_ID: &str = "243216c5-369e-4056-ac67-05388f86dc81";
+
+/// An invoice for 115.00 with `status`, with `extra` fields such as allocations added.
+fn invoice(status: &str, extra: json::Value) -> json::Value {
+    let mut invoice = json!({
+        "Type": "ACCREC",
+        "InvoiceID": INVOICE_ID,
+        "InvoiceNumber": "INV-0001",
+        "Contact": {"ContactID": "bd2270c3-8706-4c11-9cfb-000b551c3f51", "Name": "ABC Limited"},
+        "DateString": "2017-10-20T00:00:00",
+        "Status": status,
+        "LineAmountTypes": "Exclusive",
+        "SubTotal": 100.00,
+        "TotalTax": 15.00,
+        "Total": 115.00,
+        "AmountDue": 115.00,
+    });
+    for (key, value) in extra.as_object().unwrap() {
+        invoice[key] = value.clone();
+    }
+    invoice
+}
+
+fn parse(invoice: json::Value) -> Invoice {
+    json::from_value(invoice).unwrap()
+}
+
+fn decimal(value: &str) -> BigDecimal {
+    BigDecimal::from_str(value).unwrap()
+}
+
+#[test]
+fn check_invoice_transitions() {
+    assert_eq!(parse(invoice("DRAFT", json!({}))).check_transition(InvoiceStatus::Deleted), Ok(()));
+    assert_eq!(parse(invoice("AUTHORISED", json!({}))).check_transition(InvoiceStatus::Voided), Ok(()));
+    assert_eq!(parse(invoice("AUTHORISED", json!({}))).check_transition(InvoiceStatus::Deleted),
+               Err(InvoiceError::InvalidTransition{from: InvoiceStatus::Authorised, to: InvoiceStatus::Deleted}));
+    assert_eq!(parse(invoice("PAID", json!({}))).check_transition(InvoiceStatus::Voided),
+               Err(InvoiceError::InvalidTransition{from: InvoiceStatus::Paid, to: InvoiceStatus::Voided}));
+}
+
+#[test]
+fn allocations_prevent_voiding() {
+    let paid = json!({"AmountPaid": 0.01, "AmountDue": 114.99});
+    let credited = json!({"AmountCredited": 15.00, "AmountDue": 100.00});
+    let credit_note = json!({"CreditNotes": [{
+        "CreditNoteID": "d8d2b1e4-7f7b-4b87-8c3c-3f0e3a4e5b61",
+        "AppliedAmount": 15.00
+    }]});
+    let overpayment = json!({"Overpayments": [{
+        "OverpaymentID": "6a1f0b2c-3d4e-4f5a-8b6c-7d8e9f0a1b2c",
+        "AppliedAmount": 20.00
+    }]});
+    for extra in vec![paid, credited, credit_note, overpayment] {
+        let invoice = parse(invoice("AUTHORISED", extra.clone()));
+        assert_eq!(invoice.check_transition(InvoiceStatus::Voided), Err(InvoiceError::HasPayments), "{}", extra);
+    }
+}
+
+#[test]
+fn update_checks_status_changes() {
+    // Each update only fetches the invoice, so there's nothing scripted for a write
+    let current = json!({"Invoices": [invoice("AUTHORISED", json!({"AmountCredited": 15.00}))]}).to_string();
+    let script = Script::new(vec![current.clone(), current]);
+    let client = script.client();
+    let id: InvoiceId = INVOICE_ID.parse().unwrap();
+    let contact_id: ContactId = "bd2270c3-8706-4c11-9cfb-000b551c3f51".parse().unwrap();
+    let params = |status| {
+        let line_item = LineItemParams::builder("Consulting").build();
+        InvoiceParams::builder(InvoiceType::AccountsReceivable, contact_id, line_item).status(status).build()
+    };
+
+    match Invoice::update(&client, id, params(InvoiceStatus::Draft), None) {
+        Err(Error::Invoice(InvoiceError::InvalidTransition{from, to}, None)) => {
+            assert_eq!((from, to), (InvoiceStatus::Authorised, InvoiceStatus::Draft));
+        }
+        other => panic!("expected an invalid transition, got {:?}", other.map(|i| i.status)),
+    }
+    match Invoice::update(&client, id, params(InvoiceStatus::Voided), None) {
+        Err(Error::Invoice(InvoiceError::HasPayments, None)) => {}
+        other => panic!("expected credit to prevent voiding, got {:?}", other.map(|i| i.status)),
+    }
+}
+
+#[test]
+fn status_changes_ignore_cached_invoices() {
+    let body = |status| json!({"Invoices": [invoice(status, json!({}))]}).to_string();
+    let script = Script::new(vec![body("DRAFT"), body("PAID"), body("AUTHORISED"), body("DRAFT"), body("DELETED")]);
+    let cache = Arc::new(ResponseCache::new(Duration::from_secs(300)));
+    let client = script.client().with_cache(cache, "org-1");
+    let id: InvoiceId = INVOICE_ID.parse().unwrap();
+
+    // Paid elsewhere since it was cached as a draft
+    assert_eq!(Invoice::get(&client, id).unwrap().status, InvoiceStatus::Draft);
+    match Invoice::delete(&client, id, None) {
+        Err(Error::Invoice(InvoiceError::InvalidTransition{from, ..}, None)) => assert_eq!(from, InvoiceStatus::Paid),
+        other => panic!("expected the paid invoice not to be deleted, got {:?}", other.map(|i| i.status)),
+    }
+
+    // Put back to draft elsewhere since it was cached as authorised
+    let client = script.client().with_cache(Arc::new(ResponseCache::new(Duration::from_secs(300))), "org-1");
+    assert_eq!(Invoice::get(&client, id).unwrap().status, InvoiceStatus::Authorised);
+    assert_eq!(Invoice::delete(&client, id, None).unwrap().status, InvoiceStatus::Deleted);
+    assert_eq!(script.requests().len(), 5);
+}
+
+#[test]
+fn convert_to_base_currency() {
+    let usd = parse(invoice("AUTHORISED", json!({"CurrencyCode": "USD", "CurrencyRate": 0.75})));
+    assert_eq!(usd.base_currency_total(), Some(decimal("153.33")));
+    assert_eq!(usd.to_base_currency(&decimal("30")), Some(decimal("40")));
+
+    let unrated = parse(invoice("AUTHORISED", json!({"CurrencyCode": "USD"})));
+    assert_eq!(unrated.base_currency_total(), None);
+    let zero = parse(invoice("AUTHORISED", json!({"CurrencyCode": "USD", "CurrencyRate": 0})));
+    assert_eq!(zero.base_currency_total(), None);
+
+    assert_eq!(to_base_currency(&decimal("10"), &decimal("3")), Some(decimal("3.33")));
+    assert_eq!(to_base_currency(&decimal("10"), &decimal("0")), None);
+}
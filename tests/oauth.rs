@@ -0,0 +1,70 @@
+extern crate openssl;
+extern crate percent_encoding;
+extern crate rustc_serialize;
+extern crate xero;
+
+use openssl::hash::MessageDigest;
+use openssl::sign::Verifier;
+use percent_encoding::percent_decode;
+use rustc_serialize::base64::FromBase64;
+use std::collections::HashMap;
+use xero::{Application, PKey, PrivateApplication, Rsa};
+
+/// Parses the `key="value"` pairs of an `OAuth ...` authorization header.
+fn header_params(header: &str) -> HashMap<String, String> {
+    assert!(header.starts_with("OAuth "));
+    header["OAuth ".len()..].split(", ").map(|pair| {
+        let mut parts = pair.splitn(2, '=');
+        let key = parts.next().unwrap().to_string();
+        let value = parts.next().unwrap().trim_matches('"').to_string();
+        (key, value)
+    }).collect()
+}
+
+/// Percent encodes a value as OAuth requires, leaving only unreserved characters.
+fn encode(value: &str) -> String {
+    value.bytes().map(|b| match b {
+        b'A'...b'Z' | b'a'...b'z' | b'0'...b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+        _ => format!("%{:02X}", b),
+    }).collect()
+}
+
+/// Signs `url` and checks the signature matches the RFC 5849 signature base `params`.
+fn assert_signs(url: &str, base_url: &str, params: &[&str]) {
+    let rsa = Rsa::generate(1024).unwrap();
+    let public_key = PKey::from_rsa(Rsa::public_key_from_pem(&rsa.public_key_to_pem().unwrap()).unwrap()).unwrap();
+    let application = PrivateApplication::new("consumer", PKey::from_rsa(rsa).unwrap()).unwrap();
+    let header = application.get_signature("GET", url).unwrap();
+    let oauth = header_params(&header);
+
+    let mut normalized: Vec<String> = params.iter().map(|param| param.to_string()).collect();
+    normalized.push(String::from("oauth_consumer_key=consumer"));
+    normalized.push(format!("oauth_nonce={}", oauth["oauth_nonce"]));
+    normalized.push(String::from("oauth_signature_method=RSA-SHA1"));
+    normalized.push(format!("oauth_timestamp={}", oauth["oauth_timestamp"]));
+    normalized.push(String::from("oauth_token=consumer"));
+    normalized.push(String::from("oauth_version=1.0"));
+    normalized.sort();
+    let base = format!("GET&{}&{}", encode(base_url), encode(&normalized.join("&")));
+
+    let signature = percent_decode(oauth["oauth_signature"].as_bytes()).decode_utf8().unwrap();
+    let signature = signature.from_base64().unwrap();
+    let mut verifier = Verifier::new(MessageDigest::sha1(), &public_key).unwrap();
+    verifier.update(base.as_bytes()).unwrap();
+    assert!(verifier.finish(&signature).unwrap(), "signature doesn't match base string {}", base);
+}
+
+#[test]
+fn sign_request_without_query() {
+    assert_signs("https://api.xero.com/api.xro/2.0/Contacts", "https://api.xero.com/api.xro/2.0/Contacts", &[]);
+}
+
+#[test]
+fn sign_request_with_query() {
+    // Query parameters are decoded, re-encoded with spaces as %20 and sorted with the oauth ones
+    assert_signs(
+        "https://api.xero.com/api.xro/2.0/Contacts?where=Name%20%3D%3D%20%22A+B%22&summarizeErrors=false",
+        "https://api.xero.com/api.xro/2.0/Contacts",
+        &["summarizeErrors=false", "where=Name%20%3D%3D%20%22A%20B%22"],
+    );
+}
@@ -0,0 +1,69 @@
+extern crate xero;
+
+mod common;
+
+use common::{respond, Script};
+use std::sync::{Arc, Mutex};
+use xero::accounting::*;
+use xero::{Observer, RequestEvent};
+
+const CONTACTS: &[u8] = br#"{"Contacts":[]}"#;
+const REJECTED: &[u8] = br#"{"ErrorNumber":10,"Type":"ValidationException","Message":"A validation exception occurred","Elements":[]}"#;
+
+#[derive(Debug, PartialEq)]
+struct Observed {
+    method: String,
+    headers: Vec<(String, String)>,
+    body_size: usize,
+    status: Option<u16>,
+    response_size: usize,
+}
+
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<Observed>>>);
+
+impl Observer for Recorder {
+    fn observe(&self, event: &RequestEvent) {
+        self.0.lock().unwrap().push(Observed{
+            method: event.method.to_string(),
+            headers: event.headers.to_vec(),
+            body_size: event.body_size,
+            status: event.response.map(|meta| meta.status),
+            response_size: event.response_size,
+        });
+    }
+}
+
+#[test]
+fn observers_see_redacted_requests() {
+    let recorder = Recorder::default();
+    let script = Script::with_results(vec![respond(200, CONTACTS), respond(400, REJECTED)]);
+    let client = script.client().with_observer(recorder.clone());
+
+    Contacts::get(&client).unwrap();
+    let params = ContactParams::builder("ABC Limited").build();
+    assert!(Contact::put(&client, params, Some("key")).is_err());
+
+    let body_size = script.requests()[1].body.len();
+    assert!(body_size > 0);
+    let observed = recorder.0.lock().unwrap();
+    assert_eq!(*observed, vec![
+        Observed{
+            method: String::from("GET"),
+            headers: vec![(String::from("Accept"), String::from("application/json"))],
+            body_size: 0,
+            status: Some(200),
+            response_size: CONTACTS.len(),
+        },
+        Observed{
+            method: String::from("PUT"),
+            headers: vec![
+                (String::from("Accept"), String::from("application/json")),
+                (String::from("Idempotency-Key"), String::from("key")),
+            ],
+            body_size: body_size,
+            status: Some(400),
+            response_size: REJECTED.len(),
+        },
+    ]);
+}
@@ -0,0 +1,41 @@
+extern crate xero;
+
+use std::time::{Duration, Instant};
+use xero::RateLimiter;
+
+#[test]
+fn rate_limiter_counts_calls_in_a_sliding_window() {
+    let limiter = RateLimiter::new(2, Duration::from_secs(60));
+    let start = Instant::now();
+    let at = |seconds| start + Duration::from_secs(seconds);
+
+    assert_eq!(limiter.try_acquire_at(at(0)), Ok(()));
+    assert_eq!(limiter.try_acquire_at(at(10)), Ok(()));
+    assert_eq!(limiter.try_acquire_at(at(20)), Err(Duration::from_secs(40)));
+    // The first call has left the window, but the second hasn't
+    assert_eq!(limiter.try_acquire_at(at(60)), Ok(()));
+    assert_eq!(limiter.try_acquire_at(at(65)), Err(Duration::from_secs(5)));
+    assert_eq!(limiter.try_acquire_at(at(70)), Ok(()));
+}
+
+#[test]
+fn rate_limiter_waits_out_pauses() {
+    let limiter = RateLimiter::new(10, Duration::from_secs(60));
+    limiter.pause(Duration::from_secs(3600));
+
+    match limiter.try_acquire_at(Instant::now()) {
+        Err(delay) => assert!(delay > Duration::from_secs(3000) && delay <= Duration::from_secs(3600), "{:?}", delay),
+        Ok(()) => panic!("expected the paused limiter to refuse a call"),
+    }
+    assert!(limiter.try_acquire_at(Instant::now() + Duration::from_secs(3601)).is_ok());
+}
+
+#[test]
+fn rate_limiter_wait_returns_while_under_the_limit() {
+    // Two calls fit in the window, so neither should wait anywhere near the hour
+    let limiter = RateLimiter::new(2, Duration::from_secs(3600));
+    let start = Instant::now();
+    limiter.wait();
+    limiter.wait();
+    assert!(start.elapsed() < Duration::from_secs(60));
+}
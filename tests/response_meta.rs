@@ -0,0 +1,80 @@
+extern crate xero;
+
+mod common;
+
+use common::Script;
+use xero::accounting::*;
+use xero::{Error, Response};
+
+const INVOICE_ID: &str = "243216c5-369e-4056-ac67-05388f86dc81";
+const AUTHORISED: &str = r#"{"Invoices":[{"Type":"ACCREC","InvoiceID":"243216c5-369e-4056-ac67-05388f86dc81","InvoiceNumber":"INV-0001","Contact":{"ContactID":"bd2270c3-8706-4c11-9cfb-000b551c3f51","Name":"ABC Limited"},"DateString":"2017-10-20T00:00:00","Status":"AUTHORISED","LineAmountTypes":"Exclusive","Total":115.00,"TotalTax":15.00}]}"#;
+const VOIDED: &str = r#"{"Invoices":[{"Type":"ACCREC","InvoiceID":"243216c5-369e-4056-ac67-05388f86dc81","InvoiceNumber":"INV-0001","Contact":{"ContactID":"bd2270c3-8706-4c11-9cfb-000b551c3f51","Name":"ABC Limited"},"DateString":"2017-10-20T00:00:00","Status":"VOIDED","LineAmountTypes":"Exclusive","Total":115.00,"TotalTax":15.00}]}"#;
+
+/// A response identified by `correlation_id`.
+fn correlated(status: u16, correlation_id: &str, body: &str) -> Result<Response, Error> {
+    Ok(Response{
+        status: status,
+        headers: vec![(String::from("Xero-Correlation-Id"), String::from(correlation_id))],
+        body: body.as_bytes().to_vec(),
+    })
+}
+
+fn correlation_id(meta: Option<xero::ResponseMeta>) -> Option<String> {
+    meta.and_then(|meta| meta.correlation_id)
+}
+
+#[test]
+fn resource_calls_return_the_last_response_meta() {
+    let script = Script::with_results(vec![
+        correlated(204, "email", ""),
+        correlated(200, "get", AUTHORISED),
+        correlated(200, "void", VOIDED),
+    ]);
+    let client = script.client();
+    let id: InvoiceId = INVOICE_ID.parse().unwrap();
+
+    let ((), meta) = client.run_with_meta(|client| Invoice::email(client, id, None)).unwrap();
+    assert_eq!(correlation_id(meta), Some(String::from("email")));
+
+    // Voiding fetches the invoice first, so the meta is the void's
+    let (invoice, meta) = client.run_with_meta(|client| Invoice::void(client, id, None)).unwrap();
+    assert_eq!(invoice.status, InvoiceStatus::Voided);
+    assert_eq!(meta.map(|meta| (meta.status, meta.correlation_id)), Some((200, Some(String::from("void")))));
+}
+
+#[test]
+fn nested_calls_see_each_response() {
+    let script = Script::with_results(vec![
+        correlated(200, "outer", r#"{"Contacts":[]}"#),
+        correlated(200, "inner", r#"{"Contacts":[]}"#),
+    ]);
+    let client = script.client();
+
+    let ((), meta) = client.run_with_meta(|client| {
+        Contacts::get(client)?;
+        let (_, inner) = client.run_with_meta(|client| Contacts::get(client))?;
+        assert_eq!(correlation_id(inner), Some(String::from("inner")));
+        Ok(())
+    }).unwrap();
+    assert_eq!(correlation_id(meta), Some(String::from("inner")));
+
+    let ((), meta) = client.run_with_meta(|_| Ok(())).unwrap();
+    assert!(meta.is_none());
+}
+
+#[test]
+fn raw_calls_return_meta() {
+    let script = Script::with_results(vec![
+        correlated(200, "pdf", "%PDF-1.4"),
+        correlated(204, "email", ""),
+        correlated(204, "delete", ""),
+    ]);
+    let client = script.client();
+
+    let (pdf, meta) = client.get_binary_with_meta("/Invoices/1", "application/pdf").unwrap();
+    assert_eq!((pdf.as_slice(), meta.correlation_id), (&b"%PDF-1.4"[..], Some(String::from("pdf"))));
+    let meta = client.post_empty_with_meta("/Invoices/1/Email", &[], None).unwrap();
+    assert_eq!((meta.status, meta.correlation_id), (204, Some(String::from("email"))));
+    let meta = client.delete_with_meta("/ContactGroups/1/Contacts", None).unwrap();
+    assert_eq!(meta.correlation_id, Some(String::from("delete")));
+}
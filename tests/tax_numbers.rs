@@ -0,0 +1,54 @@
+extern crate xero;
+
+mod common;
+
+use common::Script;
+use xero::accounting::*;
+use xero::Error;
+
+#[test]
+fn validate_tax_numbers() {
+    assert!(TaxRegion::Australia.validate("51 824 753 556").is_ok());
+    assert!(TaxRegion::Australia.validate("51 824 753 557").is_err());
+    assert!(TaxRegion::Australia.validate("5182475355").is_err());
+
+    assert!(TaxRegion::NewZealand.validate("49-091-850").is_ok());
+    assert!(TaxRegion::NewZealand.validate("136-410-132").is_ok());
+    assert!(TaxRegion::NewZealand.validate("136-410-133").is_err());
+
+    assert!(TaxRegion::UnitedKingdom.validate("GB 999 9999 73").is_ok());
+    assert!(TaxRegion::UnitedKingdom.validate("GBGD001").is_ok());
+    assert!(TaxRegion::UnitedKingdom.validate("GB 999 9999").is_err());
+}
+
+#[test]
+fn invalid_tax_number_is_not_sent() {
+    let client = Script::default().client();
+    let params = ContactParams::builder("ABC Limited").tax_number("51 824 753 557").tax_region(TaxRegion::Australia).build();
+
+    match Contact::put(&client, params, None) {
+        Err(Error::InvalidTaxNumber(err)) => {
+            assert_eq!(err.region, TaxRegion::Australia);
+            assert_eq!(err.to_string(), r#"invalid ABN "51 824 753 557": checksum doesn't match"#);
+        }
+        other => panic!("expected an invalid tax number, got {:?}", other.map(|c| c.name)),
+    }
+}
+
+#[test]
+fn invalid_tax_number_fails_bulk_writes() {
+    let client = Script::default().client();
+    let params = vec![
+        ContactParams::builder("ABC Limited").tax_number("51 824 753 556").tax_region(TaxRegion::Australia).build(),
+        ContactParams::builder("Bayside Club").tax_number("136-410-133").tax_region(TaxRegion::NewZealand).build(),
+    ];
+
+    match Contacts::put_each(&client, params.clone(), None) {
+        Err(Error::InvalidTaxNumber(err)) => assert_eq!(err.region, TaxRegion::NewZealand),
+        other => panic!("expected an invalid tax number, got {:?}", other.map(|results| results.len())),
+    }
+    match BulkWriter::new(&client).batch_size(1).put(params, None) {
+        Err(Error::InvalidTaxNumber(err)) => assert_eq!(err.tax_number, "136-410-133"),
+        other => panic!("expected an invalid tax number, got {:?}", other.map(|results| results.len())),
+    }
+}
@@ -0,0 +1,264 @@
+#![cfg(feature = "testing")]
+
+extern crate bigdecimal;
+extern crate chrono;
+extern crate xero;
+
+use bigdecimal::BigDecimal;
+use chrono::NaiveDate;
+use std::io::Read;
+use std::str::FromStr;
+use xero::accounting::*;
+use xero::testing::FakeXero;
+use xero::{Error, RequestError};
+
+fn invoice(contact_id: ContactId, status: InvoiceStatus) -> InvoiceParams {
+    let line_item = LineItemParams::builder("Consulting")
+        .quantity(2.0)
+        .unit_amount(BigDecimal::from_str("50.00").unwrap())
+        .tax_amount(BigDecimal::from(15))
+        .build();
+    InvoiceParams::builder(InvoiceType::AccountsReceivable, contact_id, line_item)
+        .date(NaiveDate::from_ymd(2017, 10, 18))
+        .status(status)
+        .build()
+}
+
+#[test]
+fn fake_creates_and_gets_contacts() {
+    let xero = FakeXero::new();
+    let client = xero.client();
+
+    let created = Contact::put(&client, ContactParams::builder("ABC Limited").is_customer(true).build(), None).unwrap();
+    let fetched = Contact::get(&client, created.contact_id).unwrap();
+    assert_eq!(fetched.name, "ABC Limited");
+    assert!(fetched.is_customer);
+    assert_eq!(xero.contacts().unwrap().len(), 1);
+
+    match Contact::put(&client, ContactParams::builder("abc limited").build(), None) {
+        Err(Error::Xero(RequestError::Validation(error), meta)) => {
+            assert_eq!(meta.status, 400);
+            assert!(error.failed_elements()[0].validation_errors[0].message.contains("already assigned"));
+        }
+        other => panic!("expected a validation error, got {:?}", other.map(|c| c.name)),
+    }
+    assert_eq!(xero.contacts().unwrap().len(), 1);
+}
+
+#[test]
+fn fake_bulk_writes_independently() {
+    let xero = FakeXero::new();
+    let client = xero.client();
+
+    let results = Items::put_each(&client, vec![
+        ItemParams::builder("WIDGET").build(),
+        ItemParams::builder("WIDGET").build(),
+    ], None).unwrap();
+    assert!(results[0].is_ok());
+    assert_eq!(results[1].as_ref().err().map(|e| e.index), Some(1));
+    assert_eq!(xero.items().unwrap().len(), 1);
+}
+
+#[test]
+fn fake_applies_invoice_workflow() {
+    let xero = FakeXero::new();
+    let client = xero.client();
+    let contact = Contact::put(&client, ContactParams::builder("Bayside Club").build(), None).unwrap();
+
+    let draft = Invoice::put(&client, invoice(contact.contact_id, InvoiceStatus::Draft), None).unwrap();
+    assert_eq!(draft.status, InvoiceStatus::Draft);
+    assert_eq!(draft.invoice_number, "INV-0001");
+    assert_eq!(draft.total, BigDecimal::from(115));
+
+    let authorised = Invoice::update(&client, draft.invoice_id, invoice(contact.contact_id, InvoiceStatus::Authorised), None).unwrap();
+    assert_eq!(authorised.status, InvoiceStatus::Authorised);
+
+    let payment = PaymentParams::builder(PaymentInvoice::InvoiceId(draft.invoice_id), PaymentAccount::Code(String::from("090")), NaiveDate::from_ymd(2017, 10, 20), BigDecimal::from(115))
+        .build();
+    let payment = Payment::put(&client, payment, None).unwrap();
+    let paid = Invoice::get(&client, draft.invoice_id).unwrap();
+    assert_eq!(paid.status, InvoiceStatus::Paid);
+    assert_eq!((paid.amount_paid, paid.amount_due), (BigDecimal::from(115), BigDecimal::from(0)));
+    assert_eq!(paid.payments[0].payment_id, payment.payment_id);
+    assert!(paid.fully_paid_on_date.is_some());
+
+    // A paid invoice can't be voided until its payment is deleted
+    assert!(Invoice::update(&client, draft.invoice_id, invoice(contact.contact_id, InvoiceStatus::Voided), None).is_err());
+    Payment::delete(&client, payment.payment_id, None).unwrap();
+    let voided = Invoice::update(&client, draft.invoice_id, invoice(contact.contact_id, InvoiceStatus::Voided), None).unwrap();
+    assert_eq!(voided.status, InvoiceStatus::Voided);
+}
+
+#[test]
+fn fake_merges_duplicate_contacts() {
+    let xero = FakeXero::new();
+    let client = xero.client();
+    let kept = Contact::put(&client, ContactParams::builder("ABC Limited").email_address("accounts@abc.example").build(), None).unwrap();
+    let by_email = Contact::put(&client, ContactParams::builder("ABC Ltd").email_address("Accounts@ABC.example").build(), None).unwrap();
+    let by_name = Contact::put(&client, ContactParams::builder("A.B.C. Limited").build(), None).unwrap();
+    Contact::put(&client, ContactParams::builder("Bayside Club").build(), None).unwrap();
+
+    let open = Invoice::put(&client, invoice(by_email.contact_id, InvoiceStatus::Authorised), None).unwrap();
+    let voided = Invoice::put(&client, invoice(by_email.contact_id, InvoiceStatus::Draft), None).unwrap();
+    Invoice::update(&client, voided.invoice_id, invoice(by_email.contact_id, InvoiceStatus::Deleted), None).unwrap();
+
+    let plans = Contacts::get(&client).unwrap().find_duplicates();
+    assert_eq!(plans.len(), 1);
+    assert_eq!(plans[0].keep, kept.contact_id);
+    assert_eq!(plans[0].duplicates.iter().map(|d| (d.contact_id, d.reason)).collect::<Vec<_>>(), vec![
+        (by_email.contact_id, DuplicateReason::EmailAddress),
+        (by_name.contact_id, DuplicateReason::Name),
+    ]);
+
+    assert_eq!(plans[0].execute(&client).unwrap(), vec![open.invoice_id]);
+    assert_eq!(Invoice::get(&client, open.invoice_id).unwrap().contact.contact_id, kept.contact_id);
+    assert_eq!(Invoice::get(&client, voided.invoice_id).unwrap().contact.contact_id, by_email.contact_id);
+    assert_eq!(Contact::get(&client, by_name.contact_id).unwrap().contact_status, ContactStatus::Archived);
+    assert!(Contacts::get(&client).unwrap().find_duplicates().is_empty());
+
+    Contact::restore(&client, by_name.contact_id, None).unwrap();
+    let changes: Vec<String> = Contact::history(&client, by_name.contact_id).unwrap().into_iter().map(|r| r.changes).collect();
+    assert_eq!(changes, vec!["Created", "Archived", "Restored"]);
+}
+
+#[test]
+fn fake_manages_contact_groups() {
+    let xero = FakeXero::new();
+    let client = xero.client();
+    let abc = Contact::put(&client, ContactParams::builder("ABC Limited").build(), None).unwrap();
+    let bayside = Contact::put(&client, ContactParams::builder("Bayside Club").build(), None).unwrap();
+
+    let group = ContactGroup::put(&client, ContactGroupParams::new("Overdue"), None).unwrap();
+    assert!(ContactGroup::put(&client, ContactGroupParams::new("overdue"), None).is_err());
+    let added = ContactGroup::add_contacts(&client, group.contact_group_id, vec![abc.contact_id, bayside.contact_id], None).unwrap();
+    assert_eq!(added.len(), 2);
+    ContactGroup::remove_contact(&client, group.contact_group_id, bayside.contact_id, None).unwrap();
+
+    let renamed = ContactGroup::rename(&client, group.contact_group_id, "Final notice", None).unwrap();
+    assert_eq!(renamed.name, "Final notice");
+    assert_eq!(Contact::get(&client, abc.contact_id).unwrap().contact_groups[0].name, "Final notice");
+
+    let contacts = Contacts::get(&client).unwrap();
+    assert!(contacts.contacts.iter().all(|contact| contact.contact_groups.is_empty()));
+    let contacts = Contacts::get_with_groups(&client).unwrap();
+    let groups = |id: ContactId| contacts.contacts.iter().find(|c| c.contact_id == id).unwrap().contact_groups.len();
+    assert_eq!((groups(abc.contact_id), groups(bayside.contact_id)), (1, 0));
+
+    ContactGroup::delete(&client, group.contact_group_id, None).unwrap();
+    assert!(Contact::get(&client, abc.contact_id).unwrap().contact_groups.is_empty());
+    assert_eq!(xero.contact_groups().unwrap()[0].status, ContactGroupStatus::Deleted);
+}
+
+#[test]
+fn fake_downloads_invoice_pdf() {
+    let xero = FakeXero::new();
+    let client = xero.client();
+    let contact = Contact::put(&client, ContactParams::builder("Bayside Club").build(), None).unwrap();
+    let invoice = Invoice::put(&client, invoice(contact.contact_id, InvoiceStatus::Authorised), None).unwrap();
+
+    let pdf = Invoice::pdf(&client, invoice.invoice_id).unwrap();
+    assert!(pdf.starts_with(b"%PDF-"));
+
+    let mut streamed = Vec::new();
+    Invoice::pdf_reader(&client, invoice.invoice_id).unwrap().read_to_end(&mut streamed).unwrap();
+    assert_eq!(streamed, pdf);
+}
+
+#[test]
+fn fake_emails_and_shares_invoices() {
+    let xero = FakeXero::new();
+    let client = xero.client();
+    let contact = Contact::put(&client, ContactParams::builder("Bayside Club").build(), None).unwrap();
+    let draft = Invoice::put(&client, invoice(contact.contact_id, InvoiceStatus::Draft), None).unwrap();
+
+    match Invoice::email(&client, draft.invoice_id, None) {
+        Err(Error::Invoice(InvoiceError::InvalidStatus, Some(meta))) => assert_eq!(meta.status, 400),
+        other => panic!("expected an invalid status, got {:?}", other),
+    }
+    match Invoice::online_url(&client, draft.invoice_id) {
+        Err(Error::Invoice(InvoiceError::InvalidStatus, _)) => {}
+        other => panic!("expected an invalid status, got {:?}", other),
+    }
+
+    let authorised = Invoice::update(&client, draft.invoice_id, invoice(contact.contact_id, InvoiceStatus::Authorised), None).unwrap();
+    match Invoice::email(&client, authorised.invoice_id, None) {
+        Err(Error::Invoice(InvoiceError::NoEmailAddress, _)) => {}
+        other => panic!("expected no email address, got {:?}", other),
+    }
+    Contact::update(&client, contact.contact_id, ContactParams::builder("Bayside Club").email_address("club@bayside.example").build(), None).unwrap();
+    Invoice::email(&client, authorised.invoice_id, None).unwrap();
+
+    let url = Invoice::online_url(&client, authorised.invoice_id).unwrap();
+    assert!(url.starts_with("https://in.xero.com/"));
+}
+
+#[test]
+fn fake_enforces_invoice_transitions() {
+    let xero = FakeXero::new();
+    let client = xero.client();
+    let contact = Contact::put(&client, ContactParams::builder("Bayside Club").build(), None).unwrap();
+    let draft = Invoice::put(&client, invoice(contact.contact_id, InvoiceStatus::Draft), None).unwrap();
+
+    assert_eq!(Invoice::submit(&client, draft.invoice_id, None).unwrap().status, InvoiceStatus::Submitted);
+    assert_eq!(Invoice::authorise(&client, draft.invoice_id, None).unwrap().status, InvoiceStatus::Authorised);
+    match Invoice::delete(&client, draft.invoice_id, None) {
+        Err(Error::Invoice(InvoiceError::InvalidTransition{from, to}, None)) => {
+            assert_eq!((from, to), (InvoiceStatus::Authorised, InvoiceStatus::Deleted));
+        }
+        other => panic!("expected an invalid transition, got {:?}", other.map(|i| i.status)),
+    }
+
+    let payment = PaymentParams::builder(PaymentInvoice::InvoiceId(draft.invoice_id), PaymentAccount::Code(String::from("090")), NaiveDate::from_ymd(2017, 10, 20), BigDecimal::from(50))
+        .build();
+    let payment = Payment::put(&client, payment, None).unwrap();
+    match Invoice::void(&client, draft.invoice_id, None) {
+        Err(Error::Invoice(InvoiceError::HasPayments, None)) => {}
+        other => panic!("expected payments to prevent voiding, got {:?}", other.map(|i| i.status)),
+    }
+    Payment::delete(&client, payment.payment_id, None).unwrap();
+    assert_eq!(Invoice::void(&client, draft.invoice_id, None).unwrap().status, InvoiceStatus::Voided);
+    assert!(!InvoiceStatus::Paid.can_transition_to(InvoiceStatus::Voided));
+}
+
+#[test]
+fn fake_invoice_totals_match_calculator() {
+    let xero = FakeXero::new();
+    let client = xero.client();
+    let contact = Contact::put(&client, ContactParams::builder("Bayside Club").build(), None).unwrap();
+    let params = invoice(contact.contact_id, InvoiceStatus::Draft);
+    let totals = InvoiceCalculator::new().calculate(&params).unwrap();
+
+    let created = Invoice::put(&client, params, None).unwrap();
+    assert!(totals.matches(&created));
+    assert_eq!(totals.total, BigDecimal::from_str("115.00").unwrap());
+}
+
+#[test]
+fn fake_bills_in_foreign_currencies() {
+    let xero = FakeXero::new();
+    let client = xero.client();
+    for code in &["AUD", "USD", "EUR", "GBP"] {
+        Currency::put(&client, CurrencyParams::new(*code), None).unwrap();
+    }
+    assert!(Currency::put(&client, CurrencyParams::new("USD"), None).is_err());
+    let currencies = Currencies::get(&client).unwrap().currencies;
+    assert_eq!(currencies.len(), 4);
+    assert_eq!(currencies[1].description, "United States Dollar");
+
+    let contact = Contact::put(&client, ContactParams::builder("Bayside Club").build(), None).unwrap();
+    let mut params = invoice(contact.contact_id, InvoiceStatus::Authorised);
+    params.currency_code = Some(String::from("JPY"));
+    assert!(Invoice::put(&client, params.clone(), None).is_err());
+
+    params.currency_code = Some(String::from("USD"));
+    params.currency_rate = Some(BigDecimal::from_str("0.75").unwrap());
+    let usd = Invoice::put(&client, params, None).unwrap();
+    assert_eq!(usd.currency_rate, Some(BigDecimal::from_str("0.75").unwrap()));
+    assert_eq!(usd.base_currency_total(), Some(BigDecimal::from_str("153.33").unwrap()));
+    assert_eq!(usd.to_base_currency(&BigDecimal::from(30)), Some(BigDecimal::from(40)));
+
+    let payment = PaymentParams::builder(PaymentInvoice::InvoiceId(usd.invoice_id), PaymentAccount::Code(String::from("090")), NaiveDate::from_ymd(2017, 10, 20), BigDecimal::from(15))
+        .build();
+    let payment = Payment::put(&client, payment, None).unwrap();
+    assert_eq!(payment.currency_rate, Some(BigDecimal::from_str("0.75").unwrap()));
+}
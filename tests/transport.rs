@@ -0,0 +1,99 @@
+extern crate xero;
+
+mod common;
+
+use common::Script;
+use std::io;
+use std::time::Duration;
+use xero::accounting::*;
+use xero::{Client, Error, HyperTransport, Response, PARTNER_BASE_URL};
+
+#[test]
+fn client_uses_base_url() {
+    let script = Script::new(vec![r#"{"Contacts":[]}"#, r#"{"Contacts":[]}"#]);
+    let client = script.client().with_base_url(PARTNER_BASE_URL);
+    Contacts::get(&client).unwrap();
+
+    let client = script.client().with_base_url("http://localhost:8080/api.xro/2.0/");
+    Contacts::get(&client).unwrap();
+
+    let urls: Vec<String> = script.requests().into_iter().map(|request| request.url).collect();
+    assert_eq!(urls, vec![
+        String::from("https://api-partner.network.xero.com/api.xro/2.0/Contacts"),
+        String::from("http://localhost:8080/api.xro/2.0/Contacts"),
+    ]);
+}
+
+#[test]
+fn transport_builder_reports_tls_errors() {
+    let transport = HyperTransport::builder()
+        .connect_timeout(Duration::from_secs(5))
+        .read_timeout(Duration::from_secs(30))
+        .proxy("proxy.example.com", 3128)
+        .build();
+    assert!(transport.is_ok());
+
+    match HyperTransport::builder().ca_file("tests/does-not-exist.pem").build() {
+        Err(Error::Tls(_)) => {}
+        other => panic!("expected a TLS error, got {:?}", other.err()),
+    }
+    match HyperTransport::builder().client_pkcs12(b"not a certificate", "secret").build() {
+        Err(Error::Tls(_)) => {}
+        other => panic!("expected a TLS error, got {:?}", other.err()),
+    }
+}
+
+fn io_error(kind: io::ErrorKind) -> Result<Response, Error> {
+    Err(Error::Io(io::Error::new(kind, "test")))
+}
+
+fn status(status: u16, headers: Vec<(String, String)>) -> Result<Response, Error> {
+    Ok(Response{status: status, headers: headers, body: br#"{"Contacts":[]}"#.to_vec()})
+}
+
+fn retrying(script: &Script) -> Client {
+    script.client()
+        .with_retries(5)
+        .with_retry_backoff(Duration::from_millis(1), Duration::from_secs(1))
+}
+
+#[test]
+fn client_retries_transient_failures() {
+    let retry_after = vec![(String::from("Retry-After"), String::from("0"))];
+    let script = Script::with_results(vec![
+        io_error(io::ErrorKind::ConnectionReset),
+        status(503, Vec::new()),
+        status(429, retry_after),
+        status(200, Vec::new()),
+    ]);
+    assert!(Contacts::get(&retrying(&script)).is_ok());
+    assert_eq!(script.requests().len(), 4);
+}
+
+#[test]
+fn client_does_not_retry_permanent_failures() {
+    let script = Script::with_results(vec![io_error(io::ErrorKind::NotFound), status(200, Vec::new())]);
+    assert!(Contacts::get(&retrying(&script)).is_err());
+    assert_eq!(script.requests().len(), 1);
+
+    // Waiting out the daily limit would block for hours
+    let retry_after = vec![(String::from("Retry-After"), String::from("3600"))];
+    let script = Script::with_results(vec![status(429, retry_after), status(200, Vec::new())]);
+    match Contacts::get(&retrying(&script)) {
+        Err(Error::Xero(_, meta)) => assert_eq!(meta.status, 429),
+        other => panic!("expected a rate limit error, got {:?}", other.map(|c| c.contacts.len())),
+    }
+    assert_eq!(script.requests().len(), 1);
+}
+
+#[test]
+fn client_caps_retry_backoff() {
+    let mut results: Vec<Result<Response, Error>> = (0..70).map(|_| io_error(io::ErrorKind::TimedOut)).collect();
+    results.push(status(200, Vec::new()));
+    let script = Script::with_results(results);
+    let client = script.client()
+        .with_retries(100)
+        .with_retry_backoff(Duration::from_millis(1), Duration::from_millis(2));
+    assert!(Contacts::get(&client).is_ok());
+    assert_eq!(script.requests().len(), 71);
+}